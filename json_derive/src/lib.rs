@@ -0,0 +1,256 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Generics, LitStr, Path};
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_json(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct Attributes {
+    rename: Option<String>,
+    default: Option<Option<Path>>,
+    skip: bool,
+    flatten: bool,
+}
+
+impl Attributes {
+    fn parse(attrs: &[syn::Attribute], allowed: &[&str], target: &str) -> Result<Attributes, Error> {
+        let mut attributes = Attributes::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+            attr.parse_nested_meta(|meta| {
+                let known = ["rename", "default", "skip", "flatten"].into_iter().find(|name| meta.path.is_ident(name));
+                if let Some(name) = known.filter(|name| !allowed.contains(name)) {
+                    return Err(Error::new_spanned(&meta.path, format!("`{}` is not supported on {}", name, target)));
+                }
+                if meta.path.is_ident("rename") {
+                    attributes.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    attributes.default = Some(match meta.input.peek(syn::Token![=]) {
+                        true => Some(meta.value()?.parse::<LitStr>()?.parse()?),
+                        false => None,
+                    });
+                } else if meta.path.is_ident("skip") {
+                    attributes.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    attributes.flatten = true;
+                } else {
+                    return Err(meta.error("unsupported json attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+fn tuple_skips(fields: &syn::FieldsUnnamed) -> Result<Vec<bool>, Error> {
+    fields.unnamed.iter().map(|field| Ok(Attributes::parse(&field.attrs, &["skip"], "tuple fields")?.skip)).collect()
+}
+
+fn bounded(generics: &Generics, bound: Path) -> Generics {
+    let mut generics = generics.clone();
+    let params = generics.type_params().map(|param| param.ident.clone()).collect::<Vec<_>>();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+fn to_json(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    Attributes::parse(&input.attrs, &[], "types")?;
+    let generics = bounded(&input.generics, parse_quote!(::json::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut statements = Vec::new();
+                for field in &fields.named {
+                    let attributes = Attributes::parse(&field.attrs, &["rename", "default", "skip", "flatten"], "fields")?;
+                    let ident = field.ident.as_ref().unwrap();
+                    let key = attributes.rename.unwrap_or_else(|| ident.to_string());
+                    if attributes.skip {
+                        continue;
+                    }
+                    statements.push(match attributes.flatten {
+                        true => quote!(::json::__private::flatten_into(&mut object, ::json::ToJson::to_json(&self.#ident));),
+                        false => quote!(object.set(#key, ::json::ToJson::to_json(&self.#ident));),
+                    });
+                }
+                quote! {
+                    let mut object = ::json::object();
+                    #(#statements)*
+                    object.into()
+                }
+            },
+            Fields::Unnamed(fields) => {
+                let skips = tuple_skips(fields)?;
+                if skips == [false] {
+                    quote!(::json::ToJson::to_json(&self.0))
+                } else {
+                    let indexes = skips.iter().enumerate().filter(|(_, skip)| !**skip).map(|(index, _)| syn::Index::from(index));
+                    quote! {
+                        let mut list = ::json::array();
+                        #(list.add(::json::ToJson::to_json(&self.#indexes));)*
+                        list.into()
+                    }
+                }
+            },
+            Fields::Unit => quote!(::json::TypeJson::Null),
+        },
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(Error::new_spanned(variant, "only unit variants are supported"));
+                }
+                let ident = &variant.ident;
+                let key = Attributes::parse(&variant.attrs, &["rename"], "enum variants")?.rename.unwrap_or_else(|| ident.to_string());
+                arms.push(quote!(#name::#ident => #key.into(),));
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        },
+        Data::Union(data) => return Err(Error::new_spanned(data.union_token, "unions are not supported")),
+    };
+    Ok(quote! {
+        impl #impl_generics ::json::ToJson for #name #ty_generics #where_clause {
+            fn to_json(&self) -> ::json::TypeJson {
+                #body
+            }
+        }
+    })
+}
+
+fn from_json(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    Attributes::parse(&input.attrs, &[], "types")?;
+    let generics = bounded(&input.generics, parse_quote!(::json::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut values = Vec::new();
+                for field in &fields.named {
+                    let attributes = Attributes::parse(&field.attrs, &["rename", "default", "skip", "flatten"], "fields")?;
+                    let ident = field.ident.as_ref().unwrap();
+                    let key = attributes.rename.unwrap_or_else(|| ident.to_string());
+                    let value = if attributes.skip {
+                        quote!(::core::default::Default::default())
+                    } else if attributes.flatten {
+                        quote!(::json::FromJson::from_json(json)?)
+                    } else {
+                        let field = quote!(::json::__private::field_from_json(object, #key)?);
+                        match attributes.default {
+                            Some(Some(path)) => quote!(#field.unwrap_or_else(#path)),
+                            Some(None) => quote!(#field.unwrap_or_default()),
+                            None => quote!(#field.ok_or_else(|| ::json::__private::missing_field(#key))?),
+                        }
+                    };
+                    values.push(quote!(#ident: #value,));
+                }
+                quote! {
+                    let object = ::json::__private::expect_object(json)?;
+                    Ok(#name {
+                        #(#values)*
+                    })
+                }
+            },
+            Fields::Unnamed(fields) => {
+                let skips = tuple_skips(fields)?;
+                if skips == [false] {
+                    quote!(Ok(#name(::json::FromJson::from_json(json)?)))
+                } else {
+                    let mut len = 0;
+                    let mut values = Vec::new();
+                    for skip in skips {
+                        values.push(match skip {
+                            true => quote!(::core::default::Default::default()),
+                            false => quote!(::json::__private::element_from_json(list, #len)?),
+                        });
+                        len += usize::from(!skip);
+                    }
+                    let expected = format!("array of length {}", len);
+                    let list = match len {
+                        0 => quote!(_),
+                        _ => quote!(list),
+                    };
+                    quote! {
+                        let #list = ::json::__private::expect_list(json, #len, #expected)?;
+                        Ok(#name(#(#values),*))
+                    }
+                }
+            },
+            Fields::Unit => quote! {
+                match json {
+                    ::json::TypeJson::Null => Ok(#name),
+                    json => Err(::json::Error::Conversion { expected: "null", found: json.type_name() }),
+                }
+            },
+        },
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            let mut keys = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(Error::new_spanned(variant, "only unit variants are supported"));
+                }
+                let ident = &variant.ident;
+                let key = Attributes::parse(&variant.attrs, &["rename"], "enum variants")?.rename.unwrap_or_else(|| ident.to_string());
+                arms.push(quote!(Some(#key) => Ok(#name::#ident),));
+                keys.push(format!("{:?}", key));
+            }
+            let expected = format!("one of {}", keys.join(", "));
+            quote! {
+                match json.as_text() {
+                    #(#arms)*
+                    _ => Err(::json::Error::Conversion { expected: #expected, found: json.type_name() }),
+                }
+            }
+        },
+        Data::Union(data) => return Err(Error::new_spanned(data.union_token, "unions are not supported")),
+    };
+    Ok(quote! {
+        impl #impl_generics ::json::FromJson for #name #ty_generics #where_clause {
+            fn from_json(json: &::json::TypeJson) -> ::core::result::Result<Self, ::json::Error> {
+                #body
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn derive_rejects_attributes() {
+        let rejected: [DeriveInput; 5] = [
+            parse_quote!(struct A(#[json(rename = "x")] u8);),
+            parse_quote!(struct A(u8, #[json(default)] u8);),
+            parse_quote!(enum A { #[json(skip)] B }),
+            parse_quote!(enum A { #[json(rename = "b", flatten)] B }),
+            parse_quote!(#[json(rename = "a")] struct A { b: u8 }),
+        ];
+        for input in &rejected {
+            assert!(to_json(input).err().unwrap().to_string().contains("is not supported on"));
+            assert!(from_json(input).is_err());
+        }
+        assert!(to_json(&parse_quote!(struct A(#[json(skip)] u8, u8);)).is_ok());
+        assert!(from_json(&parse_quote!(enum A { #[json(rename = "b")] B })).is_ok());
+    }
+}
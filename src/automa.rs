@@ -1,145 +1,166 @@
 use crate::objects as json;
+use crate::error::Error;
+use crate::pull::{Container, JsonEvent, PullParser};
 use std::collections::LinkedList;
+use std::io;
 
 use ::automa as atm;
 use atm::Linkable;
 use atm::LinkProcess;
 
-struct StoreBufferIterator<T> {
-    size: usize,
-    store: std::collections::LinkedList<char>,
-    iterator: T,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
 }
 
-impl <T: Iterator<Item=char>> StoreBufferIterator<T> {
-    pub fn new(size: usize, iter: T) -> Self {
-        StoreBufferIterator {
-            size,
-            store: std::collections::LinkedList::new(),
-            iterator: iter,
+impl Position {
+    fn start() -> Position {
+        Position {
+            line: 1,
+            column: 1,
+            offset: 0,
         }
     }
 
-    pub fn into_iter(self) -> impl Iterator<Item = char> {
-        self.store.into_iter()
-    } 
-    
-    fn store_c(&mut self, c: char) {
-        self.store.push_back(c);
-        if self.store.len() >= self.size {
-            self.store.pop_front();
+    fn advance(&mut self, c: char) {
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
         }
     }
 }
 
-impl <T: Iterator<Item=char>> Iterator for StoreBufferIterator<T> {
-    type Item = char;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.iterator.next() {
-            Some(c) => {
-                self.store_c(c);
-                Some(c)
-            },
-            None => None,
-        }
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "line {}, column {} (byte {})", self.line, self.column, self.offset)
     }
 }
 
-#[derive(Debug)]
-pub struct AutomaError {
-    message: String,
-    source: DetailError,
+pub(crate) struct PositionIterator<T> {
+    pub(crate) position: Position,
+    next: Position,
+    pub(crate) iterator: T,
 }
 
-impl AutomaError {
-    pub fn new(message: String, source: DetailError) -> AutomaError {
-        AutomaError {
-            message,
-            source,
+impl <T: Iterator<Item=char>> PositionIterator<T> {
+    pub fn new(iter: T) -> Self {
+        PositionIterator {
+            position: Position::start(),
+            next: Position::start(),
+            iterator: iter,
         }
     }
-}
 
-impl std::fmt::Display for AutomaError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.message)
+    pub(crate) fn locate(&self, error: Error) -> Error {
+        match error {
+            Error::Parser(mut error) => {
+                error.position = self.position;
+                error.into()
+            },
+            other => other,
+        }
     }
 }
 
-impl std::error::Error for AutomaError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        Some(&self.source)
+impl <T: Iterator<Item=char>> Iterator for PositionIterator<T> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.position = self.next;
+        match self.iterator.next() {
+            Some(c) => {
+                self.next.advance(c);
+                Some(c)
+            },
+            None => None,
+        }
     }
 }
 
-#[derive(Debug)]
-pub enum DetailError {
-    Parser(ParserError),
+pub(crate) type AutomaResult<T> = Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParserErrorKind {
+    UnexpectedChar(char),
+    UnterminatedString,
+    InvalidEscape,
+    InvalidSurrogate,
+    InvalidNumber,
+    InvalidUtf8,
+    TrailingData,
+    Eof,
 }
 
-impl std::fmt::Display for DetailError {
+impl std::fmt::Display for ParserErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
-            DetailError::Parser(error) => write!(f, "{}", error.message())
+            ParserErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ParserErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ParserErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            ParserErrorKind::InvalidSurrogate => write!(f, "invalid UTF-16 surrogate"),
+            ParserErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ParserErrorKind::InvalidUtf8 => write!(f, "invalid UTF-8 sequence"),
+            ParserErrorKind::TrailingData => write!(f, "trailing data after root value"),
+            ParserErrorKind::Eof => write!(f, "unexpected end of input"),
         }
     }
 }
 
-impl std::error::Error for DetailError {
-}
-
-impl From<DetailError> for AutomaError {
-    fn from(detail: DetailError) -> Self {
-        AutomaError::new("Automa error".to_string(), detail)
-    }
-}
-
-type AutomaResult<T> = Result<T, AutomaError>;
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParserError {
-    message: String,
+    kind: ParserErrorKind,
+    expected: Vec<String>,
+    position: Position,
 }
 
 impl ParserError {
-    pub fn new(message: String) -> ParserError {
+    pub fn new(kind: ParserErrorKind, expected: &[&str]) -> ParserError {
         ParserError {
-            message,
+            kind,
+            expected: expected.iter().map(|token| token.to_string()).collect(),
+            position: Position::default(),
         }
     }
 
-    pub fn message(&self) -> &str {
-        &self.message
+    pub(crate) fn unexpected(c: Option<char>, expected: &[&str]) -> ParserError {
+        match c {
+            Some(c) => ParserError::new(ParserErrorKind::UnexpectedChar(c), expected),
+            None => ParserError::new(ParserErrorKind::Eof, expected),
+        }
     }
-}
 
-impl std::fmt::Display for ParserError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.message())
+    pub fn kind(&self) -> &ParserErrorKind {
+        &self.kind
     }
-}
 
-impl From<&str> for ParserError {
-    fn from(message: &str) -> Self {
-        ParserError {
-            message: String::from(message),
-        }
+    pub fn expected(&self) -> &[String] {
+        &self.expected
     }
-}
 
-impl From<ParserError> for DetailError {
-    fn from(value: ParserError) -> Self {
-        DetailError::Parser(value)
+    pub fn position(&self) -> Position {
+        self.position
     }
 }
 
-impl From<ParserError> for AutomaError {
-    fn from(value: ParserError) -> Self {
-        Into::<DetailError>::into(value).into()
+impl std::fmt::Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{} at {}", self.kind, self.position)?;
+        match self.expected.len() {
+            0 => Ok(()),
+            1 => write!(f, ", expected {}", self.expected[0]),
+            _ => write!(f, ", expected one of {}", self.expected.join(", ")),
+        }
     }
 }
 
+impl std::error::Error for ParserError {
+}
+
 pub trait Automa {
     type Input;
     type Output;
@@ -153,11 +174,11 @@ pub trait Automa {
 
 }
 
-struct StrAutoma {   
+pub(crate) struct StrAutoma {   
 }
 
 impl StrAutoma {
-    fn new() -> StrAutoma {
+    pub(crate) fn new() -> StrAutoma {
         StrAutoma {
         }
     }
@@ -176,53 +197,117 @@ impl Automa for StrAutoma {
         enum StrAtm {
             EndStr,
         }
-        
-        type StrNode = atm::ANode<(), char, Option<Result<StrAtm, &'static str>>, LinkedList::<char>>;
+
+        struct StrContext {
+            value: LinkedList<char>,
+            code: u32,
+            high: Option<u32>,
+            digits: u8,
+        }
+
+        type StrNode = atm::ANode<(), char, Option<Result<StrAtm, ParserError>>, StrContext>;
         
         let mut n1: StrNode = atm::node();
         let mut n2: StrNode = atm::node();
         let mut n3: StrNode = atm::node();
         let mut n4: StrNode = atm::node();
+        let mut n5: StrNode = atm::node();
+        let mut n6: StrNode = atm::node();
+        let mut n7: StrNode = atm::node();
 
         let mut fail: StrNode = atm::node();
 
         n1.link(Some(&n2), atm::eq('"'));
         n2.link(Some(&n3), atm::eq('\\'));
         n2.link_function(Some(&n4), atm::eq('"'), |_,_| Some(Ok(StrAtm::EndStr)));
-        n2.link_process(None, |c,_| c != &'\\', |c, key| key.push_back(c));
-        n3.link_function(Some(&n2), |_,_| true, |c,key| {
+        n2.link_process(None, |c,_| c != &'\\', |c, ctx| ctx.value.push_back(c));
+        n3.link_process(Some(&n5), atm::eq('u'), |_, ctx| start_code(ctx));
+        n3.link_function(Some(&n2), |_,_| true, |c, ctx| {
             match c {
-                '\\' => key.push_back('\\'),
-                'n' => key.push_back('\n'),
-                'r' => key.push_back('\r'),
-                't' => key.push_back('\t'),
-                '"' => key.push_back('"'),
-                _ => return Some(Err("Invalid escape")),
+                '\\' => ctx.value.push_back('\\'),
+                '/' => ctx.value.push_back('/'),
+                '"' => ctx.value.push_back('"'),
+                'b' => ctx.value.push_back('\u{8}'),
+                'f' => ctx.value.push_back('\u{c}'),
+                'n' => ctx.value.push_back('\n'),
+                'r' => ctx.value.push_back('\r'),
+                't' => ctx.value.push_back('\t'),
+                _ => return Some(Err(ParserError::new(ParserErrorKind::InvalidEscape, &["'\"'", "'\\'", "'/'", "'b'", "'f'", "'n'", "'r'", "'t'", "'u'"]))),
             }
             None
         });
 
-        n1.link_function(Some(&fail), |_,_| true, |_,_| Some(Err("Invalid char in node n1")));
-        n2.link_function(Some(&fail), |_,_| true, |_,_| Some(Err("Invalid char in node n2")));
-        n3.link_function(Some(&fail), |_,_| true, |_,_| Some(Err("Invalid char in node n3")));
-        n4.link_function(Some(&fail), |_,_| true, |_,_| Some(Err("Invalid char in node n4")));
-        fail.link_function(None, |_,_| true, |_,_| Some(Err("Invalid char in node fail")));
-
-        let mut cursor = atm::Cursor::new_none(LinkedList::new(), &n1);
+        n5.link_process(None, |c, ctx| c.is_ascii_hexdigit() && ctx.digits < 3, |c, ctx| {
+            ctx.code = next_code(c, ctx);
+            ctx.digits += 1;
+        });
+        n5.link_process(Some(&n6), |c, ctx| c.is_ascii_hexdigit() && ctx.high.is_none() && is_high_surrogate(next_code(*c, ctx)), |c, ctx| {
+            ctx.high = Some(next_code(c, ctx));
+        });
+        n5.link_process(Some(&n2), |c, ctx| c.is_ascii_hexdigit() && ctx.high.is_some() && is_low_surrogate(next_code(*c, ctx)), |c, ctx| {
+            let high = ctx.high.take().unwrap();
+            let code = 0x10000 + ((high - 0xD800) << 10) + (next_code(c, ctx) - 0xDC00);
+            ctx.value.push_back(char::from_u32(code).unwrap());
+        });
+        n5.link_process(Some(&n2), |c, ctx| c.is_ascii_hexdigit() && ctx.high.is_none() && !is_low_surrogate(next_code(*c, ctx)), |c, ctx| {
+            ctx.value.push_back(char::from_u32(next_code(c, ctx)).unwrap());
+        });
+        n5.link_function(Some(&fail), |c, _| c.is_ascii_hexdigit(), |_, ctx| {
+            match ctx.high {
+                Some(_) => Some(Err(ParserError::new(ParserErrorKind::InvalidSurrogate, &["low surrogate escape"]))),
+                None => Some(Err(ParserError::new(ParserErrorKind::InvalidSurrogate, &["high surrogate escape"]))),
+            }
+        });
+        n5.link_function(Some(&fail), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidEscape, &["hex digit"]))));
+
+        n6.link(Some(&n7), atm::eq('\\'));
+        n6.link_function(Some(&fail), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidSurrogate, &["low surrogate escape"]))));
+        n7.link_process(Some(&n5), atm::eq('u'), |_, ctx| start_code(ctx));
+        n7.link_function(Some(&fail), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidSurrogate, &["low surrogate escape"]))));
+
+        n1.link_function(Some(&fail), |_,_| true, |c,_| Some(Err(ParserError::unexpected(Some(c), &["'\"'"]))));
+        n2.link_function(Some(&fail), |_,_| true, |c,_| Some(Err(ParserError::unexpected(Some(c), &["character"]))));
+        n3.link_function(Some(&fail), |_,_| true, |c,_| Some(Err(ParserError::unexpected(Some(c), &["escape character"]))));
+        n4.link_function(Some(&fail), |_,_| true, |c,_| Some(Err(ParserError::unexpected(Some(c), &[]))));
+        fail.link_function(None, |_,_| true, |c,_| Some(Err(ParserError::unexpected(Some(c), &[]))));
+
+        let mut cursor = atm::Cursor::new_none(StrContext {
+            value: LinkedList::new(),
+            code: 0,
+            high: None,
+            digits: 0,
+        }, &n1);
         
         while let Some(c) = iter.next() {
             match cursor.action(c) {
-                Some(Ok(StrAtm::EndStr)) => return Ok(cursor.into_context().iter().collect()),
-                Some(Err(msg)) => return Err(ParserError::new(msg.to_string()).into()),
+                Some(Ok(StrAtm::EndStr)) => return Ok(cursor.into_context().value.iter().collect()),
+                Some(Err(error)) => return Err(error.into()),
                 _ => {},
             }
         }
         
-        Err(ParserError::new("End Str iterator".to_string()).into())
+        return Err(ParserError::new(ParserErrorKind::UnterminatedString, &["'\"'"]).into());
+
+        fn start_code(ctx: &mut StrContext) {
+            ctx.code = 0;
+            ctx.digits = 0;
+        }
+
+        fn next_code(c: char, ctx: &StrContext) -> u32 {
+            ctx.code * 16 + c.to_digit(16).unwrap_or(0)
+        }
+
+        fn is_high_surrogate(code: u32) -> bool {
+            (0xD800..=0xDBFF).contains(&code)
+        }
+
+        fn is_low_surrogate(code: u32) -> bool {
+            (0xDC00..=0xDFFF).contains(&code)
+        }
     }
 }
 
-struct StringAutoma {
+pub(crate) struct StringAutoma {
     value: String,
 }
 
@@ -250,22 +335,18 @@ impl Automa for StringAutoma {
         if let Some(v) =self.value.chars().next() { v == input} else { false }
     }
     fn start(&self, iter: &mut dyn Iterator<Item=Self::Input>) -> AutomaResult<Self::Output> {
-        let mut i = 0;
-        while let Some(c) = iter.next() {
-            if self.value.as_bytes()[i] == c as u8 {
-                i += 1;
-                if i == self.value.len() {
-                    return Ok(());
-                }
-            } else {
-                return Err(ParserError::from("Erro String match. Char not equal").into());
+        let literal = format!("'{}'", self.value);
+        for expected in self.value.chars() {
+            match iter.next() {
+                Some(c) if c == expected => continue,
+                other => return Err(ParserError::unexpected(other, &[&literal]).into()),
             }
         }
-        Err(ParserError::from("Invalid StringAutoma parse.").into())
+        Ok(())
     }
 }
 
-struct NumberAutoma;
+pub(crate) struct NumberAutoma;
 
 impl NumberAutoma {
     pub fn new() -> NumberAutoma {
@@ -276,10 +357,10 @@ impl NumberAutoma {
 
 impl Automa for NumberAutoma {
     type Input=char;
-    type Output=(f32, Option<Self::Input>);
+    type Output=(json::Number, Option<Self::Input>);
 
     fn can_start(&self, input: Self::Input) -> bool {
-        is_number(input)
+        input == '-' || is_number(input)
     }
 
     fn start(&self, iter: &mut dyn Iterator<Item=Self::Input>) -> AutomaResult<Self::Output> {
@@ -291,11 +372,13 @@ impl Automa for NumberAutoma {
         struct NumContext {
             end: bool,
             positive: bool,
+            exponent_positive: bool,
             extra: Option<char>,
             num: LinkedList::<char>,
+            exponent: LinkedList::<char>,
         }
         
-        type Node = atm::ANode<bool, char, Option<Result<NumAtm, &'static str>>, NumContext>;
+        type Node = atm::ANode<bool, char, Option<Result<NumAtm, ParserError>>, NumContext>;
 
         let mut n0: Node = atm::node();
         let mut n1: Node = atm::node();
@@ -315,15 +398,24 @@ impl Automa for NumberAutoma {
         n1.link_process(Some(&n3), |c, _| is_number(*c), |c, ctx| ctx.num.push_back(c));
 
         n2.link_process(Some(&n4), atm::eq('.'), |c, ctx| ctx.num.push_back(c));
+        n2.link(Some(&n6), |c, _| is_exponent(*c));
 
         n3.link_process(None, |c, _| is_number(*c), |c, ctx| ctx.num.push_back(c));
         n3.link_process(Some(&n4), atm::eq('.'), |c, ctx| ctx.num.push_back(c));
+        n3.link(Some(&n6), |c, _| is_exponent(*c));
         
         n4.link_process(Some(&n5), |c, _| is_number(*c), |c, ctx| ctx.num.push_back(c));
 
         n5.link_process(None, |c, _| is_number(*c), |c, ctx| ctx.num.push_back(c));
+        n5.link(Some(&n6), |c, _| is_exponent(*c));
+
+        n6.link(Some(&n7), atm::eq('+'));
+        n6.link_process(Some(&n7), atm::eq('-'), |_, ctx| ctx.exponent_positive = false);
+        n6.link_process(Some(&n8), |c, _| is_number(*c), |c, ctx| ctx.exponent.push_back(c));
+
+        n7.link_process(Some(&n8), |c, _| is_number(*c), |c, ctx| ctx.exponent.push_back(c));
 
-        // TODO
+        n8.link_process(None, |c, _| is_number(*c), |c, ctx| ctx.exponent.push_back(c));
         
         let mut err = atm::node();
         n2.link_function(Some(&err), |_,_| true, |c, ctx| {ctx.extra = Some(c); Some(Ok(NumAtm::End))});
@@ -331,20 +423,31 @@ impl Automa for NumberAutoma {
         n5.link_function(Some(&err), |_,_| true, |c, ctx| {ctx.extra = Some(c); Some(Ok(NumAtm::End))});
         n8.link_function(Some(&err), |_,_| true, |c, ctx| {ctx.extra = Some(c); Some(Ok(NumAtm::End))});
 
+        n0.link_function(Some(&err), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidNumber, &["'-'", "digit"]))));
+        n1.link_function(Some(&err), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidNumber, &["digit"]))));
+        n4.link_function(Some(&err), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidNumber, &["digit"]))));
+        n6.link_function(Some(&err), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidNumber, &["'+'", "'-'", "digit"]))));
+        n7.link_function(Some(&err), |_,_| true, |_,_| Some(Err(ParserError::new(ParserErrorKind::InvalidNumber, &["digit"]))));
+
         let mut cursor = atm::Cursor::black(NumContext {
             end: false,
             positive: true,
+            exponent_positive: true,
             num: LinkedList::new(),
+            exponent: LinkedList::new(),
             extra: None,
-        }, &n0, |_| Some(Err("Invalid input, default")));
+        }, &n0, |_| Some(Err(ParserError::new(ParserErrorKind::InvalidNumber, &[]))));
 
+        let mut text = String::new();
         while let Some(c) = iter.next() {
+            text.push(c);
             match cursor.action(c) {
                 Some(Ok(NumAtm::End)) => {
+                    text.pop();
                     let ctx = cursor.into_context();
-                    return Ok((retrieve_num(&ctx)?, ctx.extra))
+                    return Ok((retrieve_num(&ctx, text)?, ctx.extra))
                 },
-                Some(Err(msg)) => return Err(ParserError::new(msg.to_string()).into()),
+                Some(Err(error)) => return Err(error.into()),
                 _ => {},
             }
         }
@@ -353,351 +456,170 @@ impl Automa for NumberAutoma {
 
         let ctx = cursor.into_context();
         if ctx.end {
-            return Ok((retrieve_num(&ctx)?, None));
+            return Ok((retrieve_num(&ctx, text)?, None));
         } else {
-            return Err(ParserError::new("Invalid number...".to_string()).into());
+            return Err(ParserError::new(ParserErrorKind::Eof, &["digit"]).into());
         }
 
-        fn retrieve_num(ctx: &NumContext) -> Result<f32, ParserError> {
-            let sign = if ctx.positive { 1f32 } else { -1f32 };
-            Ok(ctx.num
-                .iter()
-                .collect::<String>()
-                .parse()
-                .map_err(|err: std::num::ParseFloatError| ParserError::new(err.to_string()))?).map(|el: f32| el * sign)
+        #[cfg(feature = "arbitrary_precision")]
+        fn retrieve_num(_: &NumContext, text: String) -> Result<json::Number, ParserError> {
+            Ok(json::Number::verbatim(text))
         }
-    }
-}
 
-enum ObjectAtm {
-    N1, N2, N3, N4, N5,
-}
-
-struct ObjectAutoma {
-}
+        #[cfg(not(feature = "arbitrary_precision"))]
+        fn retrieve_num(ctx: &NumContext, _: String) -> Result<json::Number, ParserError> {
+            let sign = if ctx.positive { "" } else { "-" };
+            let mantissa = format!("{}{}", sign, ctx.num.iter().collect::<String>());
+            let exponent = if ctx.exponent.is_empty() {
+                None
+            } else {
+                let sign = if ctx.exponent_positive { 1 } else { -1 };
+                let digits = ctx.exponent.iter().collect::<String>().parse().unwrap_or(i64::MAX);
+                Some(json::NumberExponent::wide(digits * sign))
+            };
+            json::Number::from_decimal(&mantissa, exponent).ok_or_else(|| ParserError::new(ParserErrorKind::InvalidNumber, &[]))
+        }
 
-impl ObjectAutoma {
-    fn new() -> ObjectAutoma {
-        ObjectAutoma {
+        fn is_exponent(c: char) -> bool {
+            c == 'e' || c == 'E'
         }
     }
 }
 
-impl Automa for ObjectAutoma {
-    type Input = char;
-    type Output = json::ObjectJson;
-
-    fn can_start(&self, input: Self::Input) -> bool {
-        input == '{'
-    }
+fn build_tree<I: Iterator<Item=char>>(events: &mut PullParser<I>) -> AutomaResult<json::TypeJson> {
+    build_next(events).unwrap_or_else(|| Err(ParserError::new(ParserErrorKind::Eof, &["value"]).into()))
+}
 
-    fn start(&self, iter: &mut dyn Iterator<Item=Self::Input>) -> AutomaResult<Self::Output> {
-        let mut iter: Box<dyn Iterator<Item=char>> = Box::new(std::iter::empty().chain(iter));
-        let mut status = ObjectAtm::N1;
-        let mut key = None;
-        let mut json_object = json::object();
-        let mut key_pipe = std::collections::LinkedList::new();
-        let mut object_pipe = std::collections::LinkedList::new();
-        let str_automa = StrAutoma::new();
-        let array_automa = ArrayAutoma::new();
-        let number_automa = NumberAutoma::new();
-        let null_automa = StringAutoma::from("null");
-        let true_automa = StringAutoma::from("true");
-        let false_automa = StringAutoma::from("false");
-        while let Some(c) = iter.next() {
-            match status {
-                ObjectAtm::N1 => {
-                    match c {
-                        '{' => {
-                            status = ObjectAtm::N2;
-                        },
-                        _ => return Err(ParserError::from("invalid from node: N1").into())
-                    }
-                },
-                ObjectAtm::N2 => {
-                    let str_automa = StrAutoma::new();
-                    match c {
-                        c if is_space(c) => continue,
-                        c if str_automa.can_start(c) => {
-                            let result = str_automa.process(c, &mut iter);
-                            match result {
-                                Ok(k) => {
-                                    key = Some(k);
-                                }
-                                Err(msg) => return Err(msg),
-                            }
-                            status = ObjectAtm::N3;
-                        },
-                        _ => return Err(ParserError::from("invalid from node: N2").into())
-                    }
-                },
-                ObjectAtm::N3 => {
-                    match c {
-                        c if is_space(c) => continue,
-                        ':' => {
-                            status = ObjectAtm::N4;
-                        },
-                        other => return Err(ParserError::new(format!("invalid from node: N3. Value: {other}")).into())
-                    }
-                },
-                ObjectAtm::N4 => {
-                    match c {
-                        c if is_space(c) => continue,
-                        '{' => {
-                            object_pipe.push_front(json_object);
-                            key_pipe.push_front(key.take().unwrap());
-                            json_object = json::object();
-                            status = ObjectAtm::N2;
-                        },
-                        c if array_automa.can_start(c) => {
-                            let result = array_automa.process(c, &mut iter);
-                            match result {
-                                Ok(value) => {
-                                    json_object.set(&key.take().unwrap(), value);
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                            status = ObjectAtm::N5;
-                        },
-                        c if null_automa.can_start(c) => {
-                            let result = null_automa.process(c, &mut iter);
-                            match result {
-                                Ok(_) => {
-                                    json_object.set(&key.take().unwrap(), json::null());
-                                    status = ObjectAtm::N5;
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                        },
-                        c if true_automa.can_start(c) => {
-                            let result = true_automa.process(c, &mut iter);
-                            match result {
-                                Ok(_) => {
-                                    json_object.set(&key.take().unwrap(), true);
-                                    status = ObjectAtm::N5;
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                        },
-                        c if false_automa.can_start(c) => {
-                            let result = false_automa.process(c, &mut iter);
-                            match result {
-                                Ok(_) => {
-                                    json_object.set(&key.take().unwrap(), false);
-                                    status = ObjectAtm::N5;
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                        },
-                        c if str_automa.can_start(c) => {
-                            let result = str_automa.process(c, &mut iter);
-                            match result {
-                                Ok(value) => {
-                                    json_object.set(&key.take().unwrap(), value);
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                            status = ObjectAtm::N5;
-                        },
-                        c if number_automa.can_start(c) => {
-                            let result = number_automa.process(c, &mut iter);
-                            match result {
-                                Ok((number, c)) => {
-                                    json_object.set(&key.take().unwrap(), number);
-                                    status = ObjectAtm::N5;
-                                    if let Some(c) = c {
-                                        iter = Box::new(std::iter::once(c).chain(iter));
-                                    }
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                        }
-                        _ => return Err(ParserError::from("invalid from node: N4").into())
-                    }
-                },
-                ObjectAtm::N5 => {
-                    match c {
-                        c if is_space(c) => continue,
-                        '}' => {
-                            match object_pipe.pop_front() {
-                                Some(mut obj) => {
-                                    obj.set(&key_pipe.pop_front().unwrap(), json_object);
-                                    json_object = obj;
-                                    status = ObjectAtm::N5;
-                                },
-                                None => return Ok(json_object),
-                            }
-                        }
-                        ',' => {
-                            status = ObjectAtm::N2;
-                        }
-                        _ => return Err(ParserError::from("invalid from node: N5").into())
-                    }
-                },
-            }
+pub(crate) fn build_next<I: Iterator<Item=char>>(events: &mut PullParser<I>) -> Option<AutomaResult<json::TypeJson>> {
+    let mut containers: Vec<json::TypeJson> = Vec::new();
+    let mut keys: Vec<String> = Vec::new();
+    while let Some(event) = events.next() {
+        let value = match event {
+            Err(error) => return Some(Err(error)),
+            Ok(JsonEvent::StartObject) => {
+                containers.push(json::object().into());
+                continue;
+            },
+            Ok(JsonEvent::StartArray) => {
+                containers.push(json::array().into());
+                continue;
+            },
+            Ok(JsonEvent::Key(key)) => {
+                keys.push(key);
+                continue;
+            },
+            Ok(JsonEvent::Value(value)) => value,
+            Ok(JsonEvent::EndObject | JsonEvent::EndArray) => containers.pop().unwrap(),
+        };
+        match containers.last_mut() {
+            Some(json::TypeJson::Object(object)) => object.set(&keys.pop().unwrap(), value),
+            Some(json::TypeJson::List(list)) => list.add(value),
+            _ => return Some(Ok(value)),
         }
-        Err(ParserError::from("invalid json automa").into())
     }
+    None
 }
 
-enum ArrayAtm {
+enum KeyParseQueryAtm {
     N1,
     N2,
     N3,
+    N4,
+    N5,
+    N6,
 }
 
-pub struct ArrayAutoma;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    path: String,
+    offset: usize,
+    found: Option<char>,
+    expected: Vec<String>,
+}
 
-impl ArrayAutoma {
-    pub fn new() -> ArrayAutoma {
-        ArrayAutoma {}
+impl PathError {
+    pub(crate) fn new(offset: usize, found: Option<char>, expected: &[&str]) -> PathError {
+        PathError {
+            path: String::new(),
+            offset,
+            found,
+            expected: expected.iter().map(|token| token.to_string()).collect(),
+        }
     }
-}
 
-impl Automa for ArrayAutoma {
-    type Input = char;
-    type Output = json::ListJson;
+    pub(crate) fn with_path(mut self, path: &str) -> PathError {
+        self.path = String::from(path);
+        self
+    }
 
-    fn can_start(&self, input: Self::Input) -> bool {
-        input == '['
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
-    fn start(&self, iter: &mut dyn Iterator<Item=Self::Input>) -> AutomaResult<Self::Output> {
-        let mut iter: Box<dyn Iterator<Item=char>> = Box::new(std::iter::empty().chain(iter));
-        let mut status = ArrayAtm::N1;
-        let mut json_array = json::array();
-        let string_automa = StrAutoma::new();
-        let number_automa = NumberAutoma::new();
-        let object_automa = ObjectAutoma::new();
-        let array_automa = ArrayAutoma::new();
-        let null_automa = StringAutoma::from("null");
-        let false_automa = StringAutoma::from("false");
-        let true_automa = StringAutoma::from("true");
-        while let Some(c) = iter.next() {
-            match status {
-                ArrayAtm::N1 => {
-                    match c {
-                        '[' => {
-                            status = ArrayAtm::N2;
-                        },
-                        _ => return Err(ParserError::from("Invalid ArrayAtm::N1").into()),
-                    }
-                },
-                ArrayAtm::N2 => {
-                    match c {
-                        ']' => return Ok(json_array),
-                        c if is_space(c) => {},
-                        c if string_automa.can_start(c) => match string_automa.process(c, &mut iter) {
-                            Ok(string) => {
-                                json_array.add(string);
-                                status = ArrayAtm::N3;
-                            },
-                            _ => return Err(ParserError::from("Invalid ArrayAtm::N2, string_automa").into()),
-                        },
-                        c if number_automa.can_start(c) => match number_automa.process(c, &mut iter) {
-                            Ok((num, c)) => {
-                                json_array.add(num);
-                                if let Some(c) = c {
-                                    iter = Box::new(std::iter::once(c).chain(iter));
-                                }
-                                status = ArrayAtm::N3;
-                            }
-                            _ => return Err(ParserError::from("Invalid ArrayAtm::N2, number_automa").into()),
-                        }
-                        c if object_automa.can_start(c) => match object_automa.process(c, &mut iter) {
-                            Ok(object) => {
-                                json_array.add(object);
-                                status = ArrayAtm::N3;
-                            }
-                            _ => return Err(ParserError::from("Invalid ArrayAtm::N2, object_automa").into()),
-                        }
-                        c if array_automa.can_start(c) => match array_automa.process(c, &mut iter) {
-                            Ok(array) => {
-                                json_array.add(array);
-                                status = ArrayAtm::N3;
-                            }
-                            _ => return Err(ParserError::from("Invalid ArrayAtm::N2, array_automa").into()),
-                        }
-                        c if null_automa.can_start(c) => match null_automa.process(c, &mut iter) {
-                            Ok(_) => {
-                                json_array.add(json::null());
-                                status = ArrayAtm::N3;
-                            }
-                            _ => return Err(ParserError::from("Invalid ArrayAtm::N2, null_automa").into()),
-                        }
-                        c if true_automa.can_start(c) => {
-                            let result = true_automa.process(c, &mut iter);
-                            match result {
-                                Ok(_) => {
-                                    json_array.add(true);
-                                    status = ArrayAtm::N3;
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                        },
-                        c if false_automa.can_start(c) => {
-                            let result = false_automa.process(c, &mut iter);
-                            match result {
-                                Ok(_) => {
-                                    json_array.add(false);
-                                    status = ArrayAtm::N3;
-                                },
-                                Err(msg) => return Err(msg),
-                            }
-                        },
-                        _ => return Err(ParserError::from("Invalid ArrayAtm::N2").into()),
-                    }
-                },
-                ArrayAtm::N3 => {
-                    match c {
-                        ']' => return Ok(json_array),
-                        ',' => {
-                            status = ArrayAtm::N2;
-                        },
-                        c if is_space(c) => {},
-                        _ => return Err(ParserError::from("Invalid ArrayAtm::N3").into()),
-                    }
-                },
-            }
-        }
-        Err(ParserError::from("unable to retrieve array").into())
+    pub fn offset(&self) -> usize {
+        self.offset
     }
 
+    pub fn found(&self) -> Option<char> {
+        self.found
+    }
+
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
 }
 
-enum KeyParseQueryAtm {
-    N1,
-    N2,
-    N3,
-    N4,
-    N5,
-    N6,
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "invalid path {:?} at byte {}: ", self.path, self.offset)?;
+        match self.found {
+            Some(c) => write!(f, "unexpected character {:?}", c)?,
+            None => write!(f, "unexpected end of path")?,
+        }
+        match self.expected.len() {
+            0 => Ok(()),
+            1 => write!(f, ", expected {}", self.expected[0]),
+            _ => write!(f, ", expected one of {}", self.expected.join(", ")),
+        }
+    }
 }
 
+impl std::error::Error for PathError {
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyParseQueryToken {
     Key(String),
     Index(usize),
-    Error(String)
+    Error(PathError)
 }
 
-impl From<&str> for KeyParseQueryToken {
-    fn from(value: &str) -> Self {
-        KeyParseQueryToken::Error(String::from(value))
+impl std::fmt::Display for KeyParseQueryToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            KeyParseQueryToken::Key(key) if !key.is_empty() && key.chars().all(|c| is_char(c) || is_number(c)) => write!(f, ".{}", key),
+            KeyParseQueryToken::Key(key) => {
+                f.write_str(".")?;
+                crate::serializer::write_escaped(f, key)
+            },
+            KeyParseQueryToken::Index(index) => write!(f, "[{}]", index),
+            KeyParseQueryToken::Error(error) => write!(f, "{}", error),
+        }
     }
 }
 
 pub struct KeyParseQueryAutoma<'a, T> {
     status: KeyParseQueryAtm,
     chars: Vec<char>,
-    iter: &'a mut T,
+    iter: PositionIterator<&'a mut T>,
 }
 
 impl <'a, T: Iterator<Item=char>> KeyParseQueryAutoma<'a, T> {
-    pub fn new(iter: &mut T) -> KeyParseQueryAutoma<T> {
+    pub fn new(iter: &'a mut T) -> KeyParseQueryAutoma<'a, T> {
         KeyParseQueryAutoma {
             status: KeyParseQueryAtm::N1,
             chars: Vec::new(),
-            iter,
+            iter: PositionIterator::new(iter),
         }
     }
 
@@ -714,9 +636,14 @@ impl <'a, T: Iterator<Item=char>> KeyParseQueryAutoma<'a, T> {
         vp.append(&mut self.chars);
         match vp.into_iter().collect::<String>().parse() {
             Ok(number) => KeyParseQueryToken::Index(number),
-            Err(msg) => KeyParseQueryToken::Error(msg.to_string()),
+            Err(_) => self.error(None, &["index within range"]),
         }
     } 
+
+    fn error(&mut self, found: Option<char>, expected: &[&str]) -> KeyParseQueryToken {
+        self.status = KeyParseQueryAtm::N6;
+        KeyParseQueryToken::Error(PathError::new(self.iter.position.offset, found, expected))
+    }
 }
 
 impl <'a, T: Iterator<Item=char>> Iterator for KeyParseQueryAutoma<'a, T> {
@@ -728,7 +655,7 @@ impl <'a, T: Iterator<Item=char>> Iterator for KeyParseQueryAutoma<'a, T> {
                 KeyParseQueryAtm::N1 => match c {
                     '.' => self.status = KeyParseQueryAtm::N2,
                     '[' => self.status = KeyParseQueryAtm::N4,
-                    _ => return Some("Invalid starter character. Valid: /".into()),
+                    _ => return Some(self.error(Some(c), &["'.'", "'['"])),
                 },
                 KeyParseQueryAtm::N2 => match c {
                     c if is_char(c) || is_number(c) => {
@@ -736,17 +663,23 @@ impl <'a, T: Iterator<Item=char>> Iterator for KeyParseQueryAutoma<'a, T> {
                         self.status = KeyParseQueryAtm::N3;
                     },
                     c if str_automa.can_start(c) => {
-                        match str_automa.process(c, self.iter) {
+                        match str_automa.process(c, &mut self.iter) {
                             Ok(key) => {
                                 self.status = KeyParseQueryAtm::N1;
                                 return Some(KeyParseQueryToken::Key(key));
                             },
-                            Err(error) => match error.source {
-                                DetailError::Parser(msg) => return Some(KeyParseQueryToken::Error(format!("Error reading {}", msg))),
-                            }
+                            Err(Error::Parser(error)) => {
+                                let found = match error.kind() {
+                                    ParserErrorKind::UnexpectedChar(c) => Some(*c),
+                                    _ => None,
+                                };
+                                let expected = error.expected().iter().map(|token| token.as_str()).collect::<Vec<_>>();
+                                return Some(self.error(found, &expected));
+                            },
+                            Err(_) => return Some(self.error(None, &["'\"'"])),
                         }
                     }
-                    _ => return Some("Invalid key string reference. Valid: char".into()),
+                    _ => return Some(self.error(Some(c), &["key character", "'\"'"])),
                 },
                 KeyParseQueryAtm::N3 => match c {
                     c if is_char(c) || is_number(c) => self.collect_c(c),
@@ -758,14 +691,14 @@ impl <'a, T: Iterator<Item=char>> Iterator for KeyParseQueryAutoma<'a, T> {
                         self.status = KeyParseQueryAtm::N4;
                         return Some(self.retrieve_field());
                     },
-                    _ => return Some("Invalid key string reference.".into()),
+                    _ => return Some(self.error(Some(c), &["key character", "'.'", "'['"])),
                 },
                 KeyParseQueryAtm::N4 => match c {
                     c if is_number(c) => {
                         self.status = KeyParseQueryAtm::N5;
                         self.collect_c(c);
                     },
-                    _ => return Some("Invalid key index reference.".into()),
+                    _ => return Some(self.error(Some(c), &["digit"])),
                 },
                 KeyParseQueryAtm::N5 => match c {
                     c if is_number(c) => self.collect_c(c),
@@ -773,7 +706,7 @@ impl <'a, T: Iterator<Item=char>> Iterator for KeyParseQueryAutoma<'a, T> {
                         self.status = KeyParseQueryAtm::N1;
                         return Some(self.retrieve_number());
                     }
-                    _ => return Some("Invalid key index reference.".into()),
+                    _ => return Some(self.error(Some(c), &["digit", "']'"])),
                 },
                 KeyParseQueryAtm::N6 => return None,
             }
@@ -783,36 +716,58 @@ impl <'a, T: Iterator<Item=char>> Iterator for KeyParseQueryAutoma<'a, T> {
             KeyParseQueryAtm::N6 => None,
             KeyParseQueryAtm::N3 => {
                 self.status = KeyParseQueryAtm::N6;
-                return Some(self.retrieve_field());
+                Some(self.retrieve_field())
             },
-            _ => Some("Invalid EOF status".into()),
+            KeyParseQueryAtm::N2 => Some(self.error(None, &["key character", "'\"'"])),
+            KeyParseQueryAtm::N4 => Some(self.error(None, &["digit"])),
+            KeyParseQueryAtm::N5 => Some(self.error(None, &["digit", "']'"])),
         }
     }
 }
 
-fn is_space(c: char) -> bool {
+pub(crate) fn is_space(c: char) -> bool {
     match c {
         ' ' | '\t' | '\n' | '\r' => true,
         _ => false,
     }
 }
 
-fn is_number(c: char) -> bool {
+pub(crate) fn is_number(c: char) -> bool {
     c >= '0' && c <= '9'
 }
 
-fn is_char(c: char) -> bool {
-    (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z')
+pub(crate) fn is_char(c: char) -> bool {
+    (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_' || !c.is_ascii()
 }
 
 pub fn parser(iter: impl Iterator<Item=char>) -> AutomaResult<json::ObjectJson> {
-    let mut buffer = StoreBufferIterator::new(10, iter);
-    match ObjectAutoma::new().start(&mut buffer) {
-        Ok(obj) => Ok(obj),
-        Err(err) => {
-            let message = format!("Stream read: {}", buffer.into_iter().collect::<String>());
-            Err(AutomaError::new(message, err.source))
-        }
+    match parse_root(PullParser::container(iter, Container::Object))? {
+        json::TypeJson::Object(object) => Ok(object),
+        _ => unreachable!(),
+    }
+}
+
+pub fn parser_value(iter: impl Iterator<Item=char>) -> AutomaResult<json::TypeJson> {
+    parse_root(PullParser::new(iter))
+}
+
+pub fn from_str(txt: &str) -> AutomaResult<json::TypeJson> {
+    parser_value(txt.chars())
+}
+
+pub fn from_slice(bytes: &[u8]) -> AutomaResult<json::TypeJson> {
+    from_reader(bytes)
+}
+
+pub fn from_reader(reader: impl io::Read) -> AutomaResult<json::TypeJson> {
+    parse_root(PullParser::from_reader(reader))
+}
+
+fn parse_root<I: Iterator<Item=char>>(mut events: PullParser<I>) -> AutomaResult<json::TypeJson> {
+    let value = build_tree(&mut events)?;
+    match events.next() {
+        Some(Err(error)) => Err(error),
+        _ => Ok(value),
     }
 }
 
@@ -858,12 +813,39 @@ mod test {
         
     }
 
+    #[test]
+    fn str_automa_escapes() {
+        let str_automa = StrAutoma::new();
+
+        let input = r#""\/\b\f\n\r\t\"\\""#;
+        assert_eq!("/\u{8}\u{c}\n\r\t\"\\", str_automa.start(&mut input.chars()).unwrap());
+
+        let input = r#""\u0041\u00e9\u20AC\ud83d\ude00!""#;
+        assert_eq!("Aé€😀!", str_automa.start(&mut input.chars()).unwrap());
+
+        let assert_error = |input: &str, kind: ParserErrorKind, expected: &str| match str_automa.start(&mut input.chars()) {
+            Err(Error::Parser(error)) => {
+                assert_eq!(&kind, error.kind());
+                assert_eq!(expected, error.expected().join(", "));
+            },
+            _ => assert!(false),
+        };
+        assert_error(r#""\x""#, ParserErrorKind::InvalidEscape, "'\"', '\\', '/', 'b', 'f', 'n', 'r', 't', 'u'");
+        assert_error(r#""\u00g1""#, ParserErrorKind::InvalidEscape, "hex digit");
+        assert_error(r#""\ud83d""#, ParserErrorKind::InvalidSurrogate, "low surrogate escape");
+        assert_error(r#""\ud83dx""#, ParserErrorKind::InvalidSurrogate, "low surrogate escape");
+        assert_error(r#""\ud83d\n""#, ParserErrorKind::InvalidSurrogate, "low surrogate escape");
+        assert_error(r#""\ud83d\u0041""#, ParserErrorKind::InvalidSurrogate, "low surrogate escape");
+        assert_error(r#""\ud83d\ud83d""#, ParserErrorKind::InvalidSurrogate, "low surrogate escape");
+        assert_error(r#""\ude00""#, ParserErrorKind::InvalidSurrogate, "high surrogate escape");
+        assert_error(r#""abc"#, ParserErrorKind::UnterminatedString, "'\"'");
+    }
+
     #[test]
     fn json_automa() {
-        let json_autom = ObjectAutoma::new();
         let input = String::from("{\"key\":\"input_automa\"}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 assert_eq!("input_automa", if let TypeJson::Text(msg) = json_object.get("key").unwrap() {msg} else {"none"});
             },
@@ -874,7 +856,7 @@ mod test {
 
         let input = String::from("{\"key1\":\"input_automa_1\",\"key2\":\"input_automa_2\"}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 assert_eq!("input_automa_1", if let TypeJson::Text(msg) = json_object.get("key1").unwrap() {msg} else {"none"});
                 assert_eq!("input_automa_2", if let TypeJson::Text(msg) = json_object.get("key2").unwrap() {msg} else {"none"});
@@ -886,7 +868,7 @@ mod test {
 
         let input = String::from("{  \"key1\" \t : \n \"input_automa_1\"  \t,\r \"key2\":\"input_automa_2\"}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 assert_eq!("input_automa_1", if let TypeJson::Text(msg) = json_object.get("key1").unwrap() {msg} else {"none"});
                 assert_eq!("input_automa_2", if let TypeJson::Text(msg) = json_object.get("key2").unwrap() {msg} else {"none"});
@@ -898,7 +880,7 @@ mod test {
 
         let input = String::from("{\"key1\":\"input_automa_1\",\"key2\":{\"key\":\"input_automa\"}}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 assert_eq!("input_automa_1", if let TypeJson::Text(msg) = json_object.get("key1").unwrap() {msg} else {"none"});
                 let jo = json_object.get("key2").unwrap();
@@ -919,7 +901,7 @@ mod test {
 
         let input = String::from("{\"key1\":\"input_automa_1\",\"key2\": null}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 assert_eq!("input_automa_1", if let TypeJson::Text(msg) = json_object.get("key1").unwrap() {msg} else {"none"});
                 assert_eq!("null", if let TypeJson::Null = json_object.get("key2").unwrap() {"null"} else {"none"});
@@ -931,7 +913,7 @@ mod test {
 
         let input = String::from("{\"key1\":\"input_automa_1\",\"key2\": 33.2}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 assert_eq!("input_automa_1", if let TypeJson::Text(msg) = json_object.get("key1").unwrap() {msg} else {"none"});
                 assert_eq!(33.2, if let TypeJson::Number(num) = json_object.get("key2").unwrap() {num.into()} else {0.0});
@@ -943,7 +925,7 @@ mod test {
 
         let input = String::from("{\"key1\":\"input_automa_1\",\"key2\": [12]}");
 
-        match json_autom.start(&mut input.chars()) {
+        match json::parser(input.chars()) {
             Ok(json_object) => {
                 match json_object.get("key2") {
                     Some(TypeJson::List(list)) => match list.get(0) {
@@ -986,41 +968,40 @@ mod test {
 
     #[test]
     fn number_automa() {
-        use std::error::Error;
         let number_automa = NumberAutoma::new();
         
         let input = String::from("1234.2123");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Ok((number, _)) => assert_eq!(1234.2123, number),
+            Ok((number, _)) => assert_eq!(1234.2123_f32, number.into()),
             _ => assert!(false),
         }
         
         let input = String::from("1234..");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Err(msg) => assert_eq!("Invalid input, default", msg.cause().unwrap().to_string()),
+            Err(Error::Parser(error)) => assert_eq!(&ParserErrorKind::InvalidNumber, error.kind()),
             _ => assert!(false),
         }
         
         let input = String::from("0.2123");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Ok((number, _)) => assert_eq!(0.2123, number),
+            Ok((number, _)) => assert_eq!(0.2123_f32, number.into()),
             _ => assert!(false),
         }
         
         let input = String::from("0");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Ok((number, _)) => assert_eq!(0.0, number),
+            Ok((number, _)) => assert_eq!(0.0_f32, number.into()),
             _ => assert!(false),
         }
         
         let input = String::from("0.0");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Ok((number, _)) => assert_eq!(0.0, number),
+            Ok((number, _)) => assert_eq!(0.0_f32, number.into()),
             _ => assert!(false),
         }
         
@@ -1028,7 +1009,7 @@ mod test {
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
             Ok((number, Some(c))) => {
-                assert_eq!(1234.002123, number);
+                assert_eq!(1234.002123_f32, number.into());
                 assert_eq!(',', c);
             },
             _ => assert!(false),
@@ -1037,25 +1018,62 @@ mod test {
         let input = String::from("1234");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Ok((number, _)) => assert_eq!(1234f32, number),
+            Ok((number, _)) => assert_eq!(1234f32, number.into()),
             _ => assert!(false),
         }
         
         let input = String::from("-1234");
         let mut iter = input.chars();
         match number_automa.start(&mut iter) {
-            Ok((number, _)) => assert_eq!(-1234f32, number),
+            Ok((number, _)) => assert_eq!(-1234f32, number.into()),
             _ => assert!(false),
         }
+
+        let input = String::from("-0.5e-3,");
+        let mut iter = input.chars();
+        match number_automa.start(&mut iter) {
+            Ok((number, Some(c))) => {
+                assert_eq!("-0.5e-3", number.to_string());
+                assert_eq!(-0.0005_f32, number.into());
+                assert_eq!(',', c);
+            },
+            _ => assert!(false),
+        }
+
+        let input = String::from("1E+10");
+        let mut iter = input.chars();
+        match number_automa.start(&mut iter) {
+            Ok((number, None)) => {
+                #[cfg(not(feature = "arbitrary_precision"))]
+                assert_eq!("1e10", number.to_string());
+                assert_eq!(1e10_f32, number.into());
+            },
+            _ => assert!(false),
+        }
+
+        let input = String::from("0e2]");
+        let mut iter = input.chars();
+        match number_automa.start(&mut iter) {
+            Ok((number, Some(']'))) => assert_eq!("0e2", number.to_string()),
+            _ => assert!(false),
+        }
+
+        for input in ["-", "-x", "1e", "1e+", "1.e5", "2E-}"] {
+            let mut iter = input.chars();
+            assert!(number_automa.start(&mut iter).is_err());
+        }
     }
 
     #[test]
     fn array_automa() {
-        let array_automa = ArrayAutoma::new();
+        let array_automa = |iter: &mut std::str::Chars| json::parser_value(iter).map(|value| match value {
+            TypeJson::List(list) => list,
+            _ => json::array(),
+        });
 
         let input = String::from("[\"Hello, World\", null, 2234.23, {\"key\": \"Value!\"}]");
         let mut iter = input.chars();
-        let array = array_automa.start(&mut iter).unwrap();
+        let array = array_automa(&mut iter).unwrap();
         match array.get(0) {
             Some(TypeJson::Text(txt)) => assert_eq!("Hello, World", txt),
             _ => assert!(false),
@@ -1078,7 +1096,7 @@ mod test {
 
         let input = String::from("[]");
         let mut iter = input.chars();
-        let array = array_automa.start(&mut iter).unwrap();
+        let array = array_automa(&mut iter).unwrap();
         assert_eq!(0, array.len());
     }
 
@@ -1117,12 +1135,79 @@ mod test {
         let input = r##"{"key": error
         "##;
         let error = json::parser(input.chars()).err().unwrap();
-        assert_eq!("Stream read: {\"key\": e", error.to_string())
+        assert_eq!("unexpected character 'e' at line 1, column 9 (byte 8), expected value", error.to_string())
+    }
+
+    #[test]
+    fn parser_value_test() {
+        match json::parser_value("  [1, \"two\", {\"k\": null}] ".chars()) {
+            Ok(TypeJson::List(list)) => {
+                assert_eq!(3, list.len());
+                assert_eq!(Some("two"), list.get(1).unwrap().as_text());
+                assert!(list.get(2).unwrap().as_object().unwrap().get("k").unwrap().is_null());
+            },
+            _ => assert!(false),
+        }
+
+        assert_eq!(Some("text"), json::parser_value("\"text\"".chars()).unwrap().as_text());
+        assert_eq!(12.5_f32, json::parser_value(" 12.5\n".chars()).unwrap().as_number().unwrap().into());
+        assert_eq!(Some(&true), json::parser_value("true".chars()).unwrap().as_bool());
+        assert_eq!(Some(&false), json::parser_value("false".chars()).unwrap().as_bool());
+        assert!(json::parser_value("null".chars()).unwrap().is_null());
+        assert_eq!(0, json::parser_value("{ }".chars()).unwrap().as_object().unwrap().len());
+        assert_eq!(0, json::parser_value("{\"k\": {}}".chars()).unwrap().as_object().unwrap().get("k").unwrap().as_object().unwrap().len());
+
+        let object = json::parser_value("{\"a\": -3, \"b\": [-1, 2e2, -0.5E-1]}".chars()).unwrap();
+        assert_eq!("-3", object.as_object().unwrap().get("a").unwrap().as_number().unwrap().to_string());
+        let list = object.as_object().unwrap().get("b").unwrap().as_list().unwrap();
+        assert_eq!(-1_f32, list.get(0).unwrap().as_number().unwrap().into());
+        assert_eq!(200_f32, list.get(1).unwrap().as_number().unwrap().into());
+        assert_eq!(-0.05_f32, list.get(2).unwrap().as_number().unwrap().into());
+
+        assert!(json::parser_value("".chars()).is_err());
+        assert!(json::parser_value("{} {}".chars()).is_err());
+        assert!(json::parser_value("12 x".chars()).is_err());
+        assert!(json::parser_value("nullx".chars()).is_err());
+    }
+
+    #[test]
+    fn parser_bytes() {
+        let txt = "{\"name\": \"Zoë\", \"list\": [1, \"€\"]}";
+        assert_eq!(txt.replace(' ', ""), json::from_str(txt).unwrap().to_string());
+        assert_eq!(txt.replace(' ', ""), json::from_slice(txt.as_bytes()).unwrap().to_string());
+        assert_eq!(txt.replace(' ', ""), json::from_reader(std::io::Cursor::new(txt)).unwrap().to_string());
+
+        match json::from_slice(b"{\"a\":\n \"b\xe9\"}") {
+            Err(Error::Parser(error)) => {
+                assert_eq!(&ParserErrorKind::InvalidUtf8, error.kind());
+                assert_eq!(Position { line: 2, column: 4, offset: 9 }, error.position());
+            },
+            _ => assert!(false),
+        }
+        match json::from_slice(b"[1, x, \xff]") {
+            Err(Error::Parser(error)) => assert_eq!(&ParserErrorKind::UnexpectedChar('x'), error.kind()),
+            _ => assert!(false),
+        }
+        match json::from_slice(b"1 \xff") {
+            Err(Error::Parser(error)) => assert_eq!(&ParserErrorKind::InvalidUtf8, error.kind()),
+            _ => assert!(false),
+        }
+
+        struct Broken;
+        impl std::io::Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "broken"))
+            }
+        }
+        match json::from_reader(std::io::Read::chain(&b"[1, "[..], Broken)) {
+            Err(Error::Io(error)) => assert_eq!(std::io::ErrorKind::UnexpectedEof, error.kind()),
+            _ => assert!(false),
+        }
     }
 
     #[test]
     fn parser_query() {
-        let query = ".key.field[1][2].name.field1.000[001].\"txt_!!£\"[33]";
+        let query = ".key.field[1][2].name.field1.000[001].\"txt_!!£\"[33]._id.naïve";
         let mut iter = query.chars();
         let mut parser = KeyParseQueryAutoma::new(&mut iter);
         assert_key("key", &mut parser);
@@ -1135,6 +1220,29 @@ mod test {
         assert_index(1, &mut parser);
         assert_key("txt_!!£", &mut parser);
         assert_index(33, &mut parser);
+        assert_key("_id", &mut parser);
+        assert_key("naïve", &mut parser);
+        assert!(parser.next().is_none());
+
+        for (query, offset, found, expected) in [
+            ("key", 0, Some('k'), vec!["'.'", "'['"]),
+            (".a[1x]", 4, Some('x'), vec!["digit", "']'"]),
+            (".a[", 3, None, vec!["digit"]),
+            (".a-b", 2, Some('-'), vec!["key character", "'.'", "'['"]),
+            ("[99999999999999999999999]", 24, None, vec!["index within range"]),
+        ] {
+            let mut iter = query.chars();
+            let mut parser = KeyParseQueryAutoma::new(&mut iter);
+            match parser.find(|token| matches!(token, KeyParseQueryToken::Error(_))) {
+                Some(KeyParseQueryToken::Error(error)) => {
+                    assert_eq!(offset, error.offset());
+                    assert_eq!(found, error.found());
+                    assert_eq!(expected, error.expected());
+                },
+                _ => assert!(false),
+            }
+            assert!(parser.next().is_none());
+        }
 
         fn assert_key<T: Iterator<Item=char>>(expected: &str, parser: &mut KeyParseQueryAutoma<T>) {
             match parser.next() {
@@ -1152,12 +1260,54 @@ mod test {
     }
 
     #[test]
-    fn buffer_store() {
-        let mut input = StoreBufferIterator::new(10, "text".chars());
+    fn position_iterator() {
+        let mut input = PositionIterator::new("aé\nb".chars());
         
-        assert_eq!('t', input.next().unwrap());
-        assert_eq!('e', input.next().unwrap());
+        assert_eq!('a', input.next().unwrap());
+        assert_eq!(Position { line: 1, column: 1, offset: 0 }, input.position);
+        assert_eq!('é', input.next().unwrap());
+        assert_eq!('\n', input.next().unwrap());
+        assert_eq!(Position { line: 1, column: 3, offset: 3 }, input.position);
+        assert_eq!('b', input.next().unwrap());
+        assert_eq!(Position { line: 2, column: 1, offset: 4 }, input.position);
+        assert_eq!(None, input.next());
+        assert_eq!(Position { line: 2, column: 2, offset: 5 }, input.position);
+    }
 
-        assert_eq!("te", input.into_iter().collect::<String>());
+    #[test]
+    fn error_positions() {
+        let assert_error = |input: &str, kind: ParserErrorKind, position: (usize, usize, usize), message: &str| {
+            match json::parser_value(input.chars()) {
+                Err(Error::Parser(error)) => {
+                    assert_eq!(&kind, error.kind());
+                    assert_eq!(Position { line: position.0, column: position.1, offset: position.2 }, error.position());
+                    assert_eq!(message, error.to_string());
+                },
+                _ => assert!(false),
+            }
+        };
+
+        assert_error("{\n  \"a\": 1,\n  \"b\" 2\n}", ParserErrorKind::UnexpectedChar('2'), (3, 7, 18),
+            "unexpected character '2' at line 3, column 7 (byte 18), expected ':'");
+        assert_error("[1, 2", ParserErrorKind::Eof, (1, 6, 5),
+            "unexpected end of input at line 1, column 6 (byte 5), expected one of ',', ']'");
+        assert_error("[\"é", ParserErrorKind::UnterminatedString, (1, 4, 4),
+            "unterminated string at line 1, column 4 (byte 4), expected '\"'");
+        assert_error("{\"k\": 1x}", ParserErrorKind::UnexpectedChar('x'), (1, 8, 7),
+            "unexpected character 'x' at line 1, column 8 (byte 7), expected one of ',', '}'");
+        assert_error("[-a]", ParserErrorKind::InvalidNumber, (1, 3, 2),
+            "invalid number at line 1, column 3 (byte 2), expected digit");
+        assert_error("[\"\\q\"]", ParserErrorKind::InvalidEscape, (1, 4, 3),
+            "invalid escape sequence at line 1, column 4 (byte 3), expected one of '\"', '\\', '/', 'b', 'f', 'n', 'r', 't', 'u'");
+        assert_error("{} x", ParserErrorKind::TrailingData, (1, 4, 3),
+            "trailing data after root value at line 1, column 4 (byte 3), expected end of input");
+        assert_error("  ", ParserErrorKind::Eof, (1, 3, 2),
+            "unexpected end of input at line 1, column 3 (byte 2), expected value");
+        assert_error("[nulŬ]", ParserErrorKind::UnexpectedChar('Ŭ'), (1, 5, 4),
+            "unexpected character 'Ŭ' at line 1, column 5 (byte 4), expected 'null'");
+        assert_error("[1,,]", ParserErrorKind::UnexpectedChar(','), (1, 4, 3),
+            "unexpected character ',' at line 1, column 4 (byte 3), expected value");
+        assert_error("{\"a\": 1,}", ParserErrorKind::UnexpectedChar('}'), (1, 9, 8),
+            "unexpected character '}' at line 1, column 9 (byte 8), expected string");
     }
-}
\ No newline at end of file
+}
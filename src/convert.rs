@@ -0,0 +1,444 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::automa::KeyParseQueryToken;
+use crate::error::Error;
+use crate::objects::{array, object, ListJson, ObjectJson, TypeJson};
+
+pub trait ToJson {
+    fn to_json(&self) -> TypeJson;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(json: &TypeJson) -> Result<Self, Error>;
+
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+pub(crate) fn at(token: KeyParseQueryToken, error: Error) -> Error {
+    match error {
+        Error::Decode { path, error } => Error::Decode {
+            path: token.to_string() + &path,
+            error,
+        },
+        error => Error::Decode {
+            path: token.to_string(),
+            error: Box::new(error),
+        },
+    }
+}
+
+fn conversion(expected: &'static str, json: &TypeJson) -> Error {
+    Error::Conversion {
+        expected,
+        found: json.type_name(),
+    }
+}
+
+pub fn expect_object(json: &TypeJson) -> Result<&ObjectJson, Error> {
+    json.as_object().ok_or_else(|| conversion("object", json))
+}
+
+pub fn expect_list<'a>(json: &'a TypeJson, len: usize, expected: &'static str) -> Result<&'a ListJson, Error> {
+    match json.as_list() {
+        Some(list) if list.len() == len => Ok(list),
+        _ => Err(conversion(expected, json)),
+    }
+}
+
+pub fn field_from_json<T: FromJson>(object: &ObjectJson, key: &str) -> Result<Option<T>, Error> {
+    match object.get(key) {
+        Some(value) => T::from_json(value)
+            .map(Some)
+            .map_err(|error| at(KeyParseQueryToken::Key(String::from(key)), error)),
+        None => Ok(T::from_missing()),
+    }
+}
+
+pub fn element_from_json<T: FromJson>(list: &ListJson, index: usize) -> Result<T, Error> {
+    let value = list.get(index).unwrap_or(&TypeJson::Null);
+    T::from_json(value).map_err(|error| at(KeyParseQueryToken::Index(index), error))
+}
+
+pub fn missing_field(name: &str) -> Error {
+    Error::MissingField {
+        name: String::from(name),
+    }
+}
+
+pub fn flatten_into(object: &mut ObjectJson, value: TypeJson) {
+    if let TypeJson::Object(inner) = value {
+        for (key, value) in inner.iter() {
+            object.set(key, value.clone());
+        }
+    }
+}
+
+impl ToJson for TypeJson {
+    fn to_json(&self) -> TypeJson {
+        self.clone()
+    }
+}
+
+impl FromJson for TypeJson {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        Ok(json.clone())
+    }
+}
+
+macro_rules! json_integer {
+    ($($target:ty),*) => {
+        $(
+            impl ToJson for $target {
+                fn to_json(&self) -> TypeJson {
+                    (*self).into()
+                }
+            }
+
+            impl FromJson for $target {
+                fn from_json(json: &TypeJson) -> Result<Self, Error> {
+                    let number = json.as_number().ok_or_else(|| conversion(stringify!($target), json))?;
+                    number.as_i64().and_then(|number| <$target>::try_from(number).ok())
+                        .or_else(|| number.as_u64().and_then(|number| <$target>::try_from(number).ok()))
+                        .ok_or_else(|| conversion(stringify!($target), json))
+                }
+            }
+        )*
+    };
+}
+
+json_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl ToJson for f32 {
+    fn to_json(&self) -> TypeJson {
+        (*self).into()
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        match f64::from_json(json)? as f32 {
+            number if number.is_infinite() => Err(conversion("f32", json)),
+            number => Ok(number),
+        }
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> TypeJson {
+        (*self).into()
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        json.as_number().and_then(|number| number.as_f64()).ok_or_else(|| conversion("f64", json))
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> TypeJson {
+        (*self).into()
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        json.as_bool().copied().ok_or_else(|| conversion("boolean", json))
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> TypeJson {
+        self.into()
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> TypeJson {
+        self.as_str().into()
+    }
+}
+
+impl FromJson for String {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        json.as_text().map(String::from).ok_or_else(|| conversion("string", json))
+    }
+}
+
+impl <T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> TypeJson {
+        (**self).to_json()
+    }
+}
+
+impl <T: ToJson> ToJson for Box<T> {
+    fn to_json(&self) -> TypeJson {
+        (**self).to_json()
+    }
+}
+
+impl <T: FromJson> FromJson for Box<T> {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        T::from_json(json).map(Box::new)
+    }
+}
+
+impl <T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> TypeJson {
+        match self {
+            Some(value) => value.to_json(),
+            None => TypeJson::Null,
+        }
+    }
+}
+
+impl <T: FromJson> FromJson for Option<T> {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        match json {
+            TypeJson::Null => Ok(None),
+            json => T::from_json(json).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl <T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> TypeJson {
+        let mut list = array();
+        for value in self {
+            list.add(value.to_json());
+        }
+        list.into()
+    }
+}
+
+impl <T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> TypeJson {
+        self.as_slice().to_json()
+    }
+}
+
+impl <T: FromJson> FromJson for Vec<T> {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        let list = json.as_list().ok_or_else(|| conversion("array", json))?;
+        (0..list.len()).map(|index| element_from_json(list, index)).collect()
+    }
+}
+
+impl <T: ToJson, S> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> TypeJson {
+        let mut obj = object();
+        for (key, value) in self {
+            obj.set(key, value.to_json());
+        }
+        obj.into()
+    }
+}
+
+impl <T: FromJson, S: std::hash::BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        let obj = expect_object(json)?;
+        obj.iter()
+            .map(|(key, value)| T::from_json(value)
+                .map(|value| (key.clone(), value))
+                .map_err(|error| at(KeyParseQueryToken::Key(key.clone()), error)))
+            .collect()
+    }
+}
+
+impl <T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> TypeJson {
+        let mut obj = object();
+        for (key, value) in self {
+            obj.set(key, value.to_json());
+        }
+        obj.into()
+    }
+}
+
+impl <T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(json: &TypeJson) -> Result<Self, Error> {
+        let obj = expect_object(json)?;
+        obj.iter()
+            .map(|(key, value)| T::from_json(value)
+                .map(|value| (key.clone(), value))
+                .map_err(|error| at(KeyParseQueryToken::Key(key.clone()), error)))
+            .collect()
+    }
+}
+
+macro_rules! json_tuple {
+    ($($len:literal => ($($name:ident $index:tt),+)),*) => {
+        $(
+            impl <$($name: ToJson),+> ToJson for ($($name,)+) {
+                fn to_json(&self) -> TypeJson {
+                    let mut list = array();
+                    $(list.add(self.$index.to_json());)+
+                    list.into()
+                }
+            }
+
+            impl <$($name: FromJson),+> FromJson for ($($name,)+) {
+                fn from_json(json: &TypeJson) -> Result<Self, Error> {
+                    let list = expect_list(json, $len, concat!("array of length ", $len))?;
+                    Ok(($(element_from_json::<$name>(list, $index)?,)+))
+                }
+            }
+        )*
+    };
+}
+
+json_tuple! {
+    1 => (A 0),
+    2 => (A 0, B 1),
+    3 => (A 0, B 1, C 2),
+    4 => (A 0, B 1, C 2, D 3),
+    5 => (A 0, B 1, C 2, D 3, E 4),
+    6 => (A 0, B 1, C 2, D 3, E 4, F 5)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::convert::*;
+
+    fn json(text: &str) -> TypeJson {
+        crate::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn convert_values() {
+        assert_eq!("[1,-2,3.5,true,\"x\",null]", (1u8, -2i64, 3.5f64, true, "x", None::<u8>).to_json().to_string());
+        assert_eq!("[[1,2],[]]", vec![vec![1u32, 2], vec![]].to_json().to_string());
+        let map = BTreeMap::from([(String::from("b"), Some(1i32)), (String::from("a"), None)]);
+        assert_eq!("{\"a\":null,\"b\":1}", map.to_json().to_string());
+        assert_eq!(map, BTreeMap::from_json(&map.to_json()).unwrap());
+
+        assert_eq!(200u8, u8::from_json(&json("200")).unwrap());
+        assert_eq!(-5i16, i16::from_json(&json("-5")).unwrap());
+        assert_eq!(2.5f32, f32::from_json(&json("2.5")).unwrap());
+        assert_eq!(9007199254740994.0, f64::from_json(&json("9007199254740994")).unwrap());
+        assert_eq!(Some(String::from("x")), Option::<String>::from_json(&json("\"x\"")).unwrap());
+        assert_eq!(None, Option::<String>::from_json(&json("null")).unwrap());
+        assert_eq!((1u8, String::from("a")), <(u8, String)>::from_json(&json("[1, \"a\"]")).unwrap());
+        let map = HashMap::<String, Vec<bool>>::from_json(&json("{\"k\": [true, false]}")).unwrap();
+        assert_eq!(Some(&vec![true, false]), map.get("k"));
+        assert!(TypeJson::from_json(&json("[1]")).unwrap() == json("[1]"));
+    }
+
+    #[test]
+    fn convert_errors() {
+        match u8::from_json(&json("300")) {
+            Err(Error::Conversion { expected, found }) => assert_eq!(("u8", "number"), (expected, found)),
+            _ => assert!(false),
+        }
+        match BTreeMap::<String, Vec<u8>>::from_json(&json("{\"a\": [1], \"b c\": [2, \"x\"]}")) {
+            Err(Error::Decode { path, error }) => {
+                assert_eq!(".\"b c\"[1]", path);
+                assert!(matches!(*error, Error::Conversion { expected: "u8", found: "string" }));
+            },
+            _ => assert!(false),
+        }
+        let error = Vec::<(u8, bool)>::from_json(&json("[[1, true], [2, 3]]")).err().unwrap();
+        assert_eq!("at [1][1]: expected boolean, found number", error.to_string());
+        assert!(<(u8, u8)>::from_json(&json("[1]")).is_err());
+        assert!(String::from_json(&json("1")).is_err());
+        match f32::from_json(&json("-1e300")) {
+            Err(Error::Conversion { expected, found }) => assert_eq!(("f32", "number"), (expected, found)),
+            _ => assert!(false),
+        }
+        assert_eq!("at [1]: expected f32, found number", Vec::<f32>::from_json(&json("[1.5, 1e39]")).err().unwrap().to_string());
+        assert_eq!(0.0, f32::from_json(&json("1e-50")).unwrap());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn convert_derive() {
+        use crate::{FromJson, ToJson};
+
+        #[derive(ToJson, FromJson, PartialEq, Debug, Default)]
+        struct Meta {
+            created: String,
+            #[json(default)]
+            tags: Vec<String>,
+        }
+
+        #[derive(ToJson, FromJson, PartialEq, Debug)]
+        enum Level {
+            #[json(rename = "low")]
+            Low,
+            High,
+        }
+
+        #[derive(ToJson, FromJson, PartialEq, Debug)]
+        struct Point(i32, i32);
+
+        #[derive(ToJson, FromJson, PartialEq, Debug)]
+        struct Id(u64);
+
+        fn port() -> u16 {
+            80
+        }
+
+        #[derive(ToJson, FromJson, PartialEq, Debug)]
+        struct Service {
+            #[json(rename = "service-id")]
+            id: Id,
+            name: Option<String>,
+            #[json(default = "port")]
+            port: u16,
+            level: Level,
+            points: Vec<Point>,
+            #[json(skip)]
+            cache: Vec<u8>,
+            #[json(flatten)]
+            meta: Meta,
+        }
+
+        let service = Service {
+            id: Id(7),
+            name: None,
+            port: 8080,
+            level: Level::Low,
+            points: vec![Point(1, -1)],
+            cache: vec![1],
+            meta: Meta { created: String::from("today"), tags: vec![String::from("a")] },
+        };
+        let text = r#"{"service-id":7,"name":null,"port":8080,"level":"low","points":[[1,-1]],"created":"today","tags":["a"]}"#;
+        assert_eq!(text, service.to_json().to_string());
+        assert_eq!(Service { cache: Vec::new(), ..service }, Service::from_json(&json(text)).unwrap());
+
+        let minimal = Service::from_json(&json(r#"{"service-id": 1, "level": "High", "points": [], "created": "now"}"#)).unwrap();
+        assert_eq!((None, 80, Level::High), (minimal.name, minimal.port, minimal.level));
+        assert!(minimal.meta.tags.is_empty());
+
+        match Service::from_json(&json(r#"{"service-id": 1, "level": "High", "points": []}"#)) {
+            Err(Error::MissingField { name }) => assert_eq!("created", name),
+            _ => assert!(false),
+        }
+        let error = Service::from_json(&json(r#"{"service-id": 1, "level": "low", "points": [[1, 2], [3, "4"]], "created": ""}"#)).err().unwrap();
+        assert_eq!("at .points[1][1]: expected i32, found string", error.to_string());
+        let error = Service::from_json(&json(r#"{"service-id": 1, "level": "mid", "points": [], "created": ""}"#)).err().unwrap();
+        assert_eq!("at .level: expected one of \"low\", \"High\", found string", error.to_string());
+        let error = Vec::<Meta>::from_json(&json(r#"[{"created": "x"}, {}]"#)).err().unwrap();
+        assert_eq!("at [1]: missing field \"created\"", error.to_string());
+
+        #[derive(ToJson, FromJson, PartialEq, Debug)]
+        struct Tagged(String, #[json(skip)] Vec<u8>, u8);
+
+        #[derive(ToJson, FromJson, PartialEq, Debug)]
+        struct Hidden(#[json(skip)] u8);
+
+        assert_eq!("[\"a\",2]", Tagged(String::from("a"), vec![1], 2).to_json().to_string());
+        assert_eq!(Tagged(String::from("a"), Vec::new(), 2), Tagged::from_json(&json("[\"a\", 2]")).unwrap());
+        assert!(Tagged::from_json(&json("[\"a\", [1], 2]")).is_err());
+        assert_eq!("[]", Hidden(1).to_json().to_string());
+        assert_eq!(Hidden(0), Hidden::from_json(&json("[]")).unwrap());
+    }
+}
@@ -0,0 +1,574 @@
+use std::fmt;
+use std::io;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::automa::Position;
+use crate::decoder::Utf8Decoder;
+use crate::error::Error;
+use crate::objects::{array, object, ListJson, Number, ObjectJson, TypeJson};
+use crate::pull::{JsonEvent, PullParser};
+
+pub(crate) const NUMBER_TOKEN: &str = "$json::private::Number";
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+pub struct SerdeDeserializer<I> {
+    events: PullParser<I>,
+    peeked: Option<JsonEvent>,
+}
+
+impl <I: Iterator<Item=char>> SerdeDeserializer<I> {
+    pub fn new(iter: I) -> SerdeDeserializer<I> {
+        SerdeDeserializer {
+            events: PullParser::new(iter),
+            peeked: None,
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.events.position()
+    }
+
+    pub fn end(&mut self) -> Result<(), Error> {
+        match self.peeked.take().map(Ok).or_else(|| self.events.next()) {
+            None => Ok(()),
+            Some(Err(error)) => Err(error),
+            Some(Ok(_)) => Err(self.locate(de::Error::custom("trailing data after the root value"))),
+        }
+    }
+
+    fn locate(&self, error: Error) -> Error {
+        match error {
+            error @ (Error::Parser(_) | Error::Located { .. }) => error,
+            error => Error::Located {
+                position: self.position(),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    fn next_event(&mut self) -> Result<JsonEvent, Error> {
+        match self.peeked.take() {
+            Some(event) => Ok(event),
+            None => self.events.next().unwrap_or_else(|| Err(de::Error::custom("unexpected end of input"))),
+        }
+    }
+
+    fn peek_event(&mut self) -> Result<&JsonEvent, Error> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next_event()?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    fn skip_rest(&mut self) -> Result<(), Error> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_event()? {
+                JsonEvent::StartObject | JsonEvent::StartArray => depth += 1,
+                JsonEvent::EndObject | JsonEvent::EndArray => depth -= 1,
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+}
+
+impl <R: io::Read> SerdeDeserializer<Utf8Decoder<R>> {
+    pub fn from_reader(reader: R) -> SerdeDeserializer<Utf8Decoder<R>> {
+        SerdeDeserializer {
+            events: PullParser::from_reader(reader),
+            peeked: None,
+        }
+    }
+}
+
+pub fn deserialize_str<T: DeserializeOwned>(input: &str) -> Result<T, Error> {
+    let mut deserializer = SerdeDeserializer::new(input.chars());
+    let value = T::deserialize(&mut deserializer).map_err(|error| deserializer.locate(error))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+pub fn deserialize_reader<T: DeserializeOwned>(reader: impl io::Read) -> Result<T, Error> {
+    let mut deserializer = SerdeDeserializer::from_reader(reader);
+    let value = T::deserialize(&mut deserializer).map_err(|error| deserializer.locate(error))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+fn visit_number<'de, V: Visitor<'de>>(number: &Number, visitor: V) -> Result<V::Value, Error> {
+    if number.is_integer() {
+        if let Some(value) = number.as_u64() {
+            return visitor.visit_u64(value);
+        }
+        if let Some(value) = number.as_i64() {
+            return visitor.visit_i64(value);
+        }
+    }
+    match number.to_string().parse() {
+        Ok(value) => visitor.visit_f64(value),
+        Err(_) => visitor.visit_string(number.to_string()),
+    }
+}
+
+impl <I: Iterator<Item=char>> SerdeDeserializer<I> {
+    fn any<'de, V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        match self.next_event()? {
+            JsonEvent::StartObject => {
+                let mut access = Access {
+                    de: &mut *self,
+                    done: false,
+                };
+                let value = visitor.visit_map(&mut access)?;
+                if !access.done {
+                    self.skip_rest()?;
+                }
+                Ok(value)
+            },
+            JsonEvent::StartArray => {
+                let mut access = Access {
+                    de: &mut *self,
+                    done: false,
+                };
+                let value = visitor.visit_seq(&mut access)?;
+                if !access.done {
+                    self.skip_rest()?;
+                }
+                Ok(value)
+            },
+            JsonEvent::Value(TypeJson::Text(text)) => visitor.visit_string(text),
+            JsonEvent::Value(TypeJson::Number(number)) => visit_number(&number, visitor),
+            JsonEvent::Value(TypeJson::Boolean(value)) => visitor.visit_bool(value),
+            JsonEvent::Value(TypeJson::Null) => visitor.visit_unit(),
+            _ => Err(de::Error::custom("expected value")),
+        }
+    }
+
+    fn option<'de, V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        match self.peek_event()? {
+            JsonEvent::Value(TypeJson::Null) => {
+                self.next_event()?;
+                visitor.visit_none()
+            },
+            _ => visitor.visit_some(&mut *self),
+        }
+    }
+
+    fn newtype_struct<'de, V: Visitor<'de>>(&mut self, name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        if name != NUMBER_TOKEN {
+            return visitor.visit_newtype_struct(&mut *self);
+        }
+        match self.peek_event()? {
+            JsonEvent::Value(TypeJson::Number(number)) => {
+                let number = number.to_string();
+                self.next_event()?;
+                visitor.visit_map(NumberAccess(Some(number)))
+            },
+            _ => self.any(visitor),
+        }
+    }
+
+    fn enumeration<'de, V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        match self.next_event()? {
+            JsonEvent::Value(TypeJson::Text(variant)) => visitor.visit_enum(variant.into_deserializer()),
+            JsonEvent::StartObject => {
+                let value = visitor.visit_enum(&mut *self)?;
+                match self.next_event()? {
+                    JsonEvent::EndObject => Ok(value),
+                    _ => Err(de::Error::custom("expected an object with a single variant key")),
+                }
+            },
+            _ => Err(de::Error::custom("expected string or object for enum")),
+        }
+    }
+}
+
+impl <'de, I: Iterator<Item=char>> de::Deserializer<'de> for &mut SerdeDeserializer<I> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = self.any(visitor);
+        result.map_err(|error| self.locate(error))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = self.option(visitor);
+        result.map_err(|error| self.locate(error))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        let result = self.newtype_struct(name, visitor);
+        result.map_err(|error| self.locate(error))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        let result = self.enumeration(visitor);
+        result.map_err(|error| self.locate(error))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(&self.0), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl <'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16, deserialize_i32 => visit_i32, deserialize_i64 => visit_i64, deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8, deserialize_u16 => visit_u16, deserialize_u32 => visit_u32, deserialize_u64 => visit_u64, deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32, deserialize_f64 => visit_f64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(self.0))
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct NumberAccess(Option<String>);
+
+impl <'de> de::MapAccess<'de> for NumberAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.0 {
+            Some(_) => seed.deserialize(IntoDeserializer::<Error>::into_deserializer(NUMBER_TOKEN)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.0.take().unwrap_or_default()))
+    }
+}
+
+struct Access<'a, I> {
+    de: &'a mut SerdeDeserializer<I>,
+    done: bool,
+}
+
+impl <'de, 'a, I: Iterator<Item=char>> de::MapAccess<'de> for Access<'a, I> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        match self.de.next_event()? {
+            JsonEvent::Key(key) => seed.deserialize(KeyDeserializer(key)).map(Some),
+            JsonEvent::EndObject => {
+                self.done = true;
+                Ok(None)
+            },
+            _ => Err(de::Error::custom("expected object key")),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+impl <'de, 'a, I: Iterator<Item=char>> de::SeqAccess<'de> for Access<'a, I> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        if let JsonEvent::EndArray = self.de.peek_event()? {
+            self.de.next_event()?;
+            self.done = true;
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl <'de, I: Iterator<Item=char>> de::EnumAccess<'de> for &mut SerdeDeserializer<I> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        match self.next_event()? {
+            JsonEvent::Key(key) => Ok((seed.deserialize(IntoDeserializer::<Error>::into_deserializer(key))?, self)),
+            _ => Err(de::Error::custom("expected variant key")),
+        }
+    }
+}
+
+impl <'de, I: Iterator<Item=char>> de::VariantAccess<'de> for &mut SerdeDeserializer<I> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+struct TypeJsonVisitor;
+
+impl <'de> Visitor<'de> for TypeJsonVisitor {
+    type Value = TypeJson;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<TypeJson, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<TypeJson, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<TypeJson, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<TypeJson, E> {
+        if !value.is_finite() {
+            return Err(E::custom("non-finite numbers are not valid JSON"));
+        }
+        Ok(value.into())
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<TypeJson, E> {
+        Ok(value.into())
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<TypeJson, E> {
+        Ok(value.into())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<TypeJson, E> {
+        Ok(TypeJson::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<TypeJson, E> {
+        Ok(TypeJson::Null)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<TypeJson, D::Error> {
+        de::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<TypeJson, A::Error> {
+        let mut list = array();
+        while let Some(value) = seq.next_element::<TypeJson>()? {
+            list.add(value);
+        }
+        Ok(list.into())
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<TypeJson, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<TypeJson, A::Error> {
+        let mut obj = object();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == NUMBER_TOKEN && obj.is_empty() {
+                let number = map.next_value::<String>()?;
+                return match crate::from_str(&number) {
+                    Ok(TypeJson::Number(number)) => Ok(number.into()),
+                    _ => Err(de::Error::custom(format!("invalid number {:?}", number))),
+                };
+            }
+            obj.set(&key, map.next_value::<TypeJson>()?);
+        }
+        Ok(obj.into())
+    }
+}
+
+impl <'de> de::Deserialize<'de> for TypeJson {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<TypeJson, D::Error> {
+        deserializer.deserialize_newtype_struct(NUMBER_TOKEN, TypeJsonVisitor)
+    }
+}
+
+impl <'de> de::Deserialize<'de> for ObjectJson {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<ObjectJson, D::Error> {
+        match TypeJson::deserialize(deserializer)? {
+            TypeJson::Object(obj) => Ok(obj),
+            other => Err(de::Error::custom(format!("expected object, found {}", other.type_name()))),
+        }
+    }
+}
+
+impl <'de> de::Deserialize<'de> for ListJson {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<ListJson, D::Error> {
+        match TypeJson::deserialize(deserializer)? {
+            TypeJson::List(list) => Ok(list),
+            other => Err(de::Error::custom(format!("expected array, found {}", other.type_name()))),
+        }
+    }
+}
+
+impl <'de> de::Deserialize<'de> for Number {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Number, D::Error> {
+        match TypeJson::deserialize(deserializer)? {
+            TypeJson::Number(number) => Ok(number),
+            other => Err(de::Error::custom(format!("expected number, found {}", other.type_name()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::de::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: u32, h: u32 },
+        Pair(i8, i8),
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f32,
+        debug: Option<bool>,
+        tags: Vec<String>,
+        limits: HashMap<String, i64>,
+        shapes: Vec<Shape>,
+        #[serde(default)]
+        missing: u8,
+    }
+
+    const INPUT: &str = r#"{
+        "name": "svc", "port": 8080, "ratio": 0.5, "debug": null, "tags": ["a", "b"],
+        "limits": {"cpu": -2}, "unknown": {"deep": [1, {"x": []}]},
+        "shapes": ["Empty", {"Circle": 1.5}, {"Rect": {"w": 2, "h": 3}}, {"Pair": [-1, 1]}]
+    }"#;
+
+    #[test]
+    fn deserialize_structs() {
+        let config: Config = deserialize_str(INPUT).unwrap();
+        assert_eq!(Config {
+            name: String::from("svc"),
+            port: 8080,
+            ratio: 0.5,
+            debug: None,
+            tags: vec![String::from("a"), String::from("b")],
+            limits: HashMap::from([(String::from("cpu"), -2)]),
+            shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Rect { w: 2, h: 3 }, Shape::Pair(-1, 1)],
+            missing: 0,
+        }, config);
+        let from_reader: Config = deserialize_reader(INPUT.as_bytes()).unwrap();
+        assert_eq!(config, from_reader);
+
+        assert_eq!((1u8, String::from("x")), deserialize_str::<(u8, String)>("[1, \"x\"]").unwrap());
+        assert!(deserialize_str::<u8>("300").is_err());
+        assert!(deserialize_str::<Config>("{\"name\": 1}").is_err());
+        assert!(matches!(deserialize_str::<Vec<u8>>("[1"), Err(Error::Parser(_))));
+        assert!(deserialize_str::<u8>("1 2").is_err());
+        match deserialize_str::<Vec<u8>>("[1, \"a\"]") {
+            Err(Error::Located { position, error }) => {
+                assert_eq!((1, 7), (position.line, position.column));
+                assert!(error.to_string().contains("invalid type"));
+            },
+            _ => assert!(false),
+        }
+        match deserialize_str::<HashMap<String, u8>>("{\n  \"x\": \"no\"\n}") {
+            Err(error) => assert_eq!("invalid type: string \"no\", expected u8 at line 2, column 11 (byte 12)", error.to_string()),
+            _ => assert!(false),
+        }
+        match deserialize_reader::<ObjectJson>("\n[1]".as_bytes()) {
+            Err(error) => assert_eq!("expected object, found array at line 2, column 3 (byte 3)", error.to_string()),
+            _ => assert!(false),
+        }
+
+        let map = HashMap::from([(1u32, String::from("a")), (20, String::from("b"))]);
+        assert_eq!(map, deserialize_str::<HashMap<u32, String>>(&crate::serialize_to_string(&map).unwrap()).unwrap());
+        let map = deserialize_str::<HashMap<i8, bool>>("{\"-1\": true}").unwrap();
+        assert_eq!(Some(&true), map.get(&-1));
+        match deserialize_str::<HashMap<u32, bool>>("{\"x\": true}") {
+            Err(Error::Located { position, error }) => {
+                assert_eq!(4, position.column);
+                assert!(error.to_string().contains("invalid value"));
+            },
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn deserialize_values() {
+        let value: TypeJson = deserialize_str(INPUT).unwrap();
+        assert!(value == crate::from_str(INPUT).unwrap());
+        let obj: ObjectJson = deserialize_str("{\"a\": [1.5, null, true]}").unwrap();
+        assert_eq!("{\"a\":[1.5,null,true]}", obj.to_string());
+        let list: ListJson = deserialize_str("[18446744073709551615, -9223372036854775808]").unwrap();
+        assert_eq!("[18446744073709551615,-9223372036854775808]", list.to_string());
+        let number: Number = deserialize_str("2.5").unwrap();
+        assert_eq!(Some(2.5), number.as_f64());
+        assert!(deserialize_str::<ObjectJson>("[]").is_err());
+
+        let input = "[1e400, -2E-500, 123456789012345678901234567890, {\"n\": 1.5e3}]";
+        let value: TypeJson = deserialize_str(input).unwrap();
+        assert_eq!(crate::from_str(input).unwrap().to_string(), value.to_string());
+        let number: Number = deserialize_str("1e400").unwrap();
+        assert_eq!("1e400", number.to_string());
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!("[1e400,-2E-500,123456789012345678901234567890,{\"n\":1.5e3}]", value.to_string());
+    }
+}
@@ -0,0 +1,153 @@
+use std::io;
+
+use crate::automa::{ParserError, ParserErrorKind};
+use crate::error::Error;
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+pub struct Utf8Decoder<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    start: usize,
+    end: usize,
+    finished: bool,
+    error: Option<Error>,
+}
+
+impl <R: io::Read> Utf8Decoder<R> {
+    pub(crate) fn new(reader: R) -> Utf8Decoder<R> {
+        Utf8Decoder {
+            reader,
+            buffer: vec![0; BUFFER_SIZE],
+            start: 0,
+            end: 0,
+            finished: false,
+            error: None,
+        }
+    }
+
+    pub(crate) fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        while self.start == self.end {
+            match self.reader.read(&mut self.buffer) {
+                Ok(0) => return Ok(None),
+                Ok(size) => {
+                    self.start = 0;
+                    self.end = size;
+                },
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(Some(self.buffer[self.start]))
+    }
+
+    fn fail(&mut self, error: Error) -> Option<char> {
+        self.finished = true;
+        self.error = Some(error);
+        None
+    }
+
+    fn invalid(&mut self) -> Option<char> {
+        self.fail(ParserError::new(ParserErrorKind::InvalidUtf8, &[]).into())
+    }
+}
+
+impl <R: io::Read> Iterator for Utf8Decoder<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let first = match self.peek() {
+            Ok(Some(byte)) => byte,
+            Ok(None) => {
+                self.finished = true;
+                return None;
+            },
+            Err(error) => return self.fail(error.into()),
+        };
+        let width = match first {
+            0x00..=0x7f => {
+                self.start += 1;
+                return Some(first as char);
+            },
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => return self.invalid(),
+        };
+        self.start += 1;
+        let mut bytes = [first, 0, 0, 0];
+        for byte in bytes.iter_mut().take(width).skip(1) {
+            match self.peek() {
+                Ok(Some(next)) if next & 0xc0 == 0x80 => {
+                    *byte = next;
+                    self.start += 1;
+                },
+                Ok(_) => return self.invalid(),
+                Err(error) => return self.fail(error.into()),
+            }
+        }
+        match std::str::from_utf8(&bytes[..width]) {
+            Ok(txt) => txt.chars().next(),
+            Err(_) => self.invalid(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::decoder::*;
+
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        fail: bool,
+    }
+
+    impl <'a> io::Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.bytes.split_first() {
+                Some((byte, rest)) => {
+                    buf[0] = *byte;
+                    self.bytes = rest;
+                    Ok(1)
+                },
+                None if self.fail => Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn decode_utf8() {
+        let txt = "a€é𝄞\n";
+        let mut decoder = Utf8Decoder::new(Trickle { bytes: txt.as_bytes(), fail: false });
+        assert_eq!(txt, decoder.by_ref().collect::<String>());
+        assert!(decoder.take_error().is_none());
+
+        for bytes in [&b"a\xff"[..], b"a\xc3(", b"a\xe0\x80\x80", b"a\xed\xa0\x80", b"a\xf0\x9d"] {
+            let mut decoder = Utf8Decoder::new(bytes);
+            assert_eq!(Some('a'), decoder.next());
+            assert_eq!(None, decoder.next());
+            assert_eq!(None, decoder.next());
+            match decoder.take_error() {
+                Some(Error::Parser(error)) => assert_eq!(&ParserErrorKind::InvalidUtf8, error.kind()),
+                _ => assert!(false),
+            }
+        }
+
+        let mut decoder = Utf8Decoder::new(Trickle { bytes: b"ab", fail: true });
+        assert_eq!("ab", decoder.by_ref().collect::<String>());
+        match decoder.take_error() {
+            Some(Error::Io(error)) => assert_eq!(io::ErrorKind::ConnectionReset, error.kind()),
+            _ => assert!(false),
+        }
+    }
+}
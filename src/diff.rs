@@ -0,0 +1,264 @@
+use std::fmt;
+
+use crate::automa::KeyParseQueryToken;
+use crate::objects::{ListJson, TypeJson};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMode {
+    #[default]
+    Index,
+    Lcs,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Change {
+    Added {
+        path: String,
+        value: TypeJson,
+    },
+    Removed {
+        path: String,
+        value: TypeJson,
+    },
+    Changed {
+        path: String,
+        from: TypeJson,
+        to: TypeJson,
+    },
+    TypeChanged {
+        path: String,
+        from: TypeJson,
+        to: TypeJson,
+    },
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. }
+            | Change::TypeChanged { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "added {}: {}", path, value),
+            Change::Removed { path, value } => write!(f, "removed {}: {}", path, value),
+            Change::Changed { path, from, to } => write!(f, "changed {}: {} -> {}", path, from, to),
+            Change::TypeChanged { path, from, to } => write!(f, "type changed {}: {} ({}) -> {} ({})", path, from, from.type_name(), to, to.type_name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+    arrays: ArrayMode,
+}
+
+impl Diff {
+    pub fn new() -> Diff {
+        Diff {
+            arrays: ArrayMode::Index,
+        }
+    }
+
+    pub fn arrays(mut self, mode: ArrayMode) -> Self {
+        self.arrays = mode;
+        self
+    }
+
+    pub fn compare(&self, from: &TypeJson, to: &TypeJson) -> Vec<Change> {
+        let mut changes = Vec::new();
+        self.compare_values(&mut changes, &mut Vec::new(), from, to);
+        changes
+    }
+
+    fn compare_values(&self, changes: &mut Vec<Change>, path: &mut Vec<KeyParseQueryToken>, from: &TypeJson, to: &TypeJson) {
+        if from == to {
+            return;
+        }
+        match (from, to) {
+            (TypeJson::Object(from), TypeJson::Object(to)) => {
+                for (key, value) in from.iter() {
+                    path.push(KeyParseQueryToken::Key(key.clone()));
+                    match to.get(key) {
+                        Some(other) => self.compare_values(changes, path, value, other),
+                        None => changes.push(Change::Removed { path: render(path), value: value.clone() }),
+                    }
+                    path.pop();
+                }
+                for (key, value) in to.iter() {
+                    if from.get(key).is_none() {
+                        path.push(KeyParseQueryToken::Key(key.clone()));
+                        changes.push(Change::Added { path: render(path), value: value.clone() });
+                        path.pop();
+                    }
+                }
+            },
+            (TypeJson::List(from), TypeJson::List(to)) => match self.arrays {
+                ArrayMode::Index => self.compare_index(changes, path, from, to),
+                ArrayMode::Lcs => compare_lcs(changes, path, from, to),
+            },
+            (from, to) if from.type_name() == to.type_name() => changes.push(Change::Changed {
+                path: render(path),
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            (from, to) => changes.push(Change::TypeChanged {
+                path: render(path),
+                from: from.clone(),
+                to: to.clone(),
+            }),
+        }
+    }
+
+    fn compare_index(&self, changes: &mut Vec<Change>, path: &mut Vec<KeyParseQueryToken>, from: &ListJson, to: &ListJson) {
+        for (i, (value, other)) in from.iter().zip(to.iter()).enumerate() {
+            path.push(KeyParseQueryToken::Index(i));
+            self.compare_values(changes, path, value, other);
+            path.pop();
+        }
+        for (i, value) in from.iter().enumerate().skip(to.len()) {
+            path.push(KeyParseQueryToken::Index(i));
+            changes.push(Change::Removed { path: render(path), value: value.clone() });
+            path.pop();
+        }
+        for (i, value) in to.iter().enumerate().skip(from.len()) {
+            path.push(KeyParseQueryToken::Index(i));
+            changes.push(Change::Added { path: render(path), value: value.clone() });
+            path.pop();
+        }
+    }
+}
+
+fn lcs_table(from: &[&TypeJson], to: &[&TypeJson]) -> Vec<Vec<usize>> {
+    let mut table = vec![vec![0usize; to.len() + 1]; from.len() + 1];
+    for i in (0..from.len()).rev() {
+        for j in (0..to.len()).rev() {
+            table[i][j] = if from[i] == to[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    table
+}
+
+pub(crate) fn lcs_pairs(from: &[&TypeJson], to: &[&TypeJson]) -> Vec<(usize, usize)> {
+    let table = lcs_table(from, to);
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < from.len() && j < to.len() {
+        if from[i] == to[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn compare_lcs(changes: &mut Vec<Change>, path: &mut Vec<KeyParseQueryToken>, from: &ListJson, to: &ListJson) {
+    let from = from.iter().collect::<Vec<_>>();
+    let to = to.iter().collect::<Vec<_>>();
+    let table = lcs_table(&from, &to);
+    let (mut i, mut j) = (0, 0);
+    while i < from.len() || j < to.len() {
+        if i < from.len() && j < to.len() && from[i] == to[j] {
+            i += 1;
+            j += 1;
+        } else if j == to.len() || (i < from.len() && table[i + 1][j] >= table[i][j + 1]) {
+            path.push(KeyParseQueryToken::Index(i));
+            changes.push(Change::Removed { path: render(path), value: from[i].clone() });
+            path.pop();
+            i += 1;
+        } else {
+            path.push(KeyParseQueryToken::Index(j));
+            changes.push(Change::Added { path: render(path), value: to[j].clone() });
+            path.pop();
+            j += 1;
+        }
+    }
+}
+
+pub(crate) fn render(path: &[KeyParseQueryToken]) -> String {
+    path.iter().map(|token| token.to_string()).collect()
+}
+
+pub fn diff(from: &TypeJson, to: &TypeJson) -> Vec<Change> {
+    Diff::new().compare(from, to)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::*;
+
+    fn json(text: &str) -> TypeJson {
+        crate::from_str(text).unwrap()
+    }
+
+    fn lines(changes: &[Change]) -> Vec<String> {
+        changes.iter().map(|change| change.to_string()).collect()
+    }
+
+    #[test]
+    fn diff_index() {
+        let from = json(r#"{"id": 1, "name": "a", "tags": ["x", "y", "z"], "meta": {"on": true, "n": 2}, "a b": 1, "gone": null}"#);
+        let to = json(r#"{"id": 1.0, "name": "b", "tags": ["x", "q"], "meta": {"on": "yes", "n": 2}, "a b": 2, "new": [1]}"#);
+        let changes = diff(&from, &to);
+        assert_eq!(vec![
+            "changed .name: \"a\" -> \"b\"",
+            "changed .tags[1]: \"y\" -> \"q\"",
+            "removed .tags[2]: \"z\"",
+            "type changed .meta.on: true (boolean) -> \"yes\" (string)",
+            "changed .\"a b\": 1 -> 2",
+            "removed .gone: null",
+            "added .new: [1]",
+        ], lines(&changes));
+
+        for change in &changes {
+            match change {
+                Change::Added { path, value } => assert!(to.lookup(path).unwrap() == value),
+                Change::Removed { path, value } => assert!(from.lookup(path).unwrap() == value),
+                Change::Changed { path, from: old, to: new } | Change::TypeChanged { path, from: old, to: new } => {
+                    assert!(from.lookup(path).unwrap() == old);
+                    assert!(to.lookup(path).unwrap() == new);
+                },
+            }
+        }
+
+        assert!(diff(&from, &from.clone()).is_empty());
+        assert_eq!(vec!["type changed : [] (array) -> {} (object)"], lines(&diff(&json("[]"), &json("{}"))));
+        assert_eq!("", diff(&json("1"), &json("2"))[0].path());
+    }
+
+    #[test]
+    fn diff_lcs() {
+        let from = json(r#"{"list": ["a", "b", "c", "d"]}"#);
+        let to = json(r#"{"list": ["z", "a", "c", "d", "e"]}"#);
+        let lcs = Diff::new().arrays(ArrayMode::Lcs);
+        assert_eq!(vec![
+            "added .list[0]: \"z\"",
+            "removed .list[1]: \"b\"",
+            "added .list[4]: \"e\"",
+        ], lines(&lcs.compare(&from, &to)));
+        assert_eq!(vec![
+            "changed .list[0]: \"a\" -> \"z\"",
+            "changed .list[1]: \"b\" -> \"a\"",
+            "added .list[4]: \"e\"",
+        ], lines(&diff(&from, &to)));
+
+        assert_eq!(vec!["removed [0]: 1", "removed [1]: 2"], lines(&lcs.compare(&json("[1, 2]"), &json("[]"))));
+        assert_eq!(vec!["added [0]: {\"k\":1}"], lines(&lcs.compare(&json("[]"), &json("[{\"k\": 1}]"))));
+        assert!(lcs.compare(&json("[1, {\"k\": [2]}]"), &json("[1.0, {\"k\": [2]}]")).is_empty());
+    }
+}
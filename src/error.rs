@@ -0,0 +1,96 @@
+use crate::automa::{ParserError, PathError, Position};
+use crate::objects::LookupError;
+use crate::patch::PatchError;
+use crate::pointer::JsonPointer;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Parser(ParserError),
+    Path(PathError),
+    Lookup(Box<LookupError>),
+    Patch(PatchError),
+    MergeNull {
+        path: JsonPointer,
+    },
+    Custom(String),
+    Conversion {
+        expected: &'static str,
+        found: &'static str,
+    },
+    Io(std::io::Error),
+    Line {
+        line: usize,
+        error: Box<Error>,
+    },
+    Decode {
+        path: String,
+        error: Box<Error>,
+    },
+    MissingField {
+        name: String,
+    },
+    Located {
+        position: Position,
+        error: Box<Error>,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Error::Parser(error) => write!(f, "{}", error),
+            Error::Path(error) => write!(f, "{}", error),
+            Error::Lookup(error) => write!(f, "{}", error),
+            Error::Patch(error) => write!(f, "{}", error),
+            Error::Custom(message) => f.write_str(message),
+            Error::MergeNull { path } => write!(f, "merge patch cannot set \"{}\" to null", path),
+            Error::Conversion { expected, found } => write!(f, "expected {}, found {}", expected, found),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::Line { line, error } => write!(f, "line {}: {}", line, error),
+            Error::Decode { path, error } => write!(f, "at {}: {}", path, error),
+            Error::MissingField { name } => write!(f, "missing field {:?}", name),
+            Error::Located { position, error } => write!(f, "{} at {}", error, position),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Line { error, .. } | Error::Decode { error, .. } | Error::Located { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParserError> for Error {
+    fn from(value: ParserError) -> Self {
+        Error::Parser(value)
+    }
+}
+
+impl From<PathError> for Error {
+    fn from(value: PathError) -> Self {
+        Error::Path(value)
+    }
+}
+
+impl From<LookupError> for Error {
+    fn from(value: LookupError) -> Self {
+        Error::Lookup(Box::new(value))
+    }
+}
+
+impl From<PatchError> for Error {
+    fn from(value: PatchError) -> Self {
+        Error::Patch(value)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
@@ -0,0 +1,267 @@
+// I-Regexp (RFC 9485) as used by the JSONPath match() and search() functions. Unicode
+// category escapes (\p{..}, \P{..}) are not supported and make the pattern invalid.
+
+enum Atom {
+    Char(char),
+    Any,
+    Class(Vec<(char, char)>, bool),
+    Group(Vec<Vec<Piece>>),
+}
+
+struct Piece {
+    atom: Atom,
+    min: usize,
+    max: Option<usize>,
+}
+
+pub(crate) struct Regexp {
+    branches: Vec<Vec<Piece>>,
+}
+
+impl Regexp {
+    pub(crate) fn parse(pattern: &str) -> Option<Regexp> {
+        let mut parser = RegexpParser {
+            chars: pattern.chars().collect(),
+            index: 0,
+        };
+        let branches = parser.branches()?;
+        match parser.peek() {
+            None => Some(Regexp { branches }),
+            Some(_) => None,
+        }
+    }
+
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        let text = text.chars().collect::<Vec<_>>();
+        match_branches(&self.branches, &text, 0, &mut |end| end == text.len())
+    }
+
+    pub(crate) fn search(&self, text: &str) -> bool {
+        let text = text.chars().collect::<Vec<_>>();
+        (0..=text.len()).any(|start| match_branches(&self.branches, &text, start, &mut |_| true))
+    }
+}
+
+struct RegexpParser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl RegexpParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.index += 1;
+        }
+        c
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn branches(&mut self) -> Option<Vec<Vec<Piece>>> {
+        let mut branches = vec![self.branch()?];
+        while self.eat('|') {
+            branches.push(self.branch()?);
+        }
+        Some(branches)
+    }
+
+    fn branch(&mut self) -> Option<Vec<Piece>> {
+        let mut pieces = Vec::new();
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            let atom = self.atom()?;
+            let (min, max) = self.quantifier()?;
+            pieces.push(Piece { atom, min, max });
+        }
+        Some(pieces)
+    }
+
+    fn atom(&mut self) -> Option<Atom> {
+        match self.bump()? {
+            '(' => {
+                let branches = self.branches()?;
+                self.eat(')').then_some(Atom::Group(branches))
+            },
+            '.' => Some(Atom::Any),
+            '[' => self.class(),
+            '\\' => self.escape().map(Atom::Char),
+            '*' | '+' | '?' | '{' | '}' | ']' => None,
+            c => Some(Atom::Char(c)),
+        }
+    }
+
+    fn quantifier(&mut self) -> Option<(usize, Option<usize>)> {
+        match self.peek() {
+            Some('*') => {
+                self.bump();
+                Some((0, None))
+            },
+            Some('+') => {
+                self.bump();
+                Some((1, None))
+            },
+            Some('?') => {
+                self.bump();
+                Some((0, Some(1)))
+            },
+            Some('{') => {
+                self.bump();
+                let min = self.number()?;
+                let max = match self.eat(',') {
+                    true if self.peek() == Some('}') => None,
+                    true => Some(self.number().filter(|max| *max >= min)?),
+                    false => Some(min),
+                };
+                self.eat('}').then_some((min, max))
+            },
+            _ => Some((1, Some(1))),
+        }
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.index;
+        while self.peek().filter(char::is_ascii_digit).is_some() {
+            self.index += 1;
+        }
+        self.chars[start..self.index].iter().collect::<String>().parse().ok()
+    }
+
+    fn class(&mut self) -> Option<Atom> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        if self.eat('-') {
+            ranges.push(('-', '-'));
+        }
+        loop {
+            match self.peek()? {
+                ']' => {
+                    self.bump();
+                    break;
+                },
+                '-' => {
+                    self.bump();
+                    if self.peek() != Some(']') {
+                        return None;
+                    }
+                    ranges.push(('-', '-'));
+                },
+                _ => {
+                    let low = self.class_char()?;
+                    let high = match (self.peek(), self.chars.get(self.index + 1)) {
+                        (Some('-'), Some(next)) if *next != ']' => {
+                            self.bump();
+                            self.class_char()?
+                        },
+                        _ => low,
+                    };
+                    if high < low {
+                        return None;
+                    }
+                    ranges.push((low, high));
+                },
+            }
+        }
+        match ranges.is_empty() {
+            true => None,
+            false => Some(Atom::Class(ranges, negated)),
+        }
+    }
+
+    fn class_char(&mut self) -> Option<char> {
+        match self.bump()? {
+            '\\' => self.escape(),
+            '[' | ']' | '-' => None,
+            c => Some(c),
+        }
+    }
+
+    fn escape(&mut self) -> Option<char> {
+        match self.bump()? {
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            c @ ('(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{' | '|' | '}') => Some(c),
+            _ => None,
+        }
+    }
+}
+
+fn match_branches(branches: &[Vec<Piece>], text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    branches.iter().any(|pieces| match_pieces(pieces, text, pos, next))
+}
+
+fn match_pieces(pieces: &[Piece], text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    match pieces.split_first() {
+        Some((piece, rest)) => match_piece(piece, 0, text, pos, &mut |end| match_pieces(rest, text, end, next)),
+        None => next(pos),
+    }
+}
+
+fn match_piece(piece: &Piece, count: usize, text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    if piece.max.is_none_or(|max| count < max) {
+        let matched = match_atom(&piece.atom, text, pos, &mut |end| {
+            (end != pos || count < piece.min) && match_piece(piece, count + 1, text, end, next)
+        });
+        if matched {
+            return true;
+        }
+    }
+    count >= piece.min && next(pos)
+}
+
+fn match_atom(atom: &Atom, text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    let c = match atom {
+        Atom::Group(branches) => return match_branches(branches, text, pos, next),
+        _ => match text.get(pos) {
+            Some(c) => *c,
+            None => return false,
+        },
+    };
+    let matched = match atom {
+        Atom::Char(expected) => c == *expected,
+        Atom::Any => c != '\n' && c != '\r',
+        Atom::Class(ranges, negated) => ranges.iter().any(|(low, high)| (*low..=*high).contains(&c)) != *negated,
+        Atom::Group(_) => false,
+    };
+    matched && next(pos + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::iregexp::*;
+
+    #[test]
+    fn iregexp_match() {
+        let matches = |pattern: &str, text: &str| Regexp::parse(pattern).unwrap().is_match(text);
+        assert!(matches("a.c", "abc"));
+        assert!(!matches("a.c", "a\nc"));
+        assert!(!matches("b", "abc"));
+        assert!(matches("(ab|cd)*e?", "abcdab"));
+        assert!(matches("[a-c-]{2,3}", "c-a"));
+        assert!(!matches("[a-c]{2,3}", "abca"));
+        assert!(matches("[^0-9]+", "x.y"));
+        assert!(matches("^$x", "^$x"));
+        assert!(matches("(a*)*b", "aaab"));
+        assert!(matches("é{2}", "éé"));
+
+        let search = |pattern: &str, text: &str| Regexp::parse(pattern).unwrap().search(text);
+        assert!(search("b", "abc"));
+        assert!(search("x*", "abc"));
+        assert!(!search("[0-9]", "abc"));
+
+        for pattern in ["(", "a)", "*", "a{2,1}", "[]", "[b-a]", "\\p{L}", "\\d", "a{", "[a"] {
+            assert!(Regexp::parse(pattern).is_none(), "{}", pattern);
+        }
+    }
+}
@@ -1,8 +1,96 @@
+extern crate self as json;
+
+mod error;
 mod objects;
 mod automa;
+mod pretty;
+mod serializer;
+mod decoder;
+mod pull;
+mod visitor;
+mod lines;
+mod stream;
+mod query;
+mod iregexp;
+mod pointer;
+mod patch;
+mod merge;
+mod diff;
+mod three_way;
+mod convert;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+mod ser;
 
 pub use objects::*;
 
+pub use crate::error::Error;
+
 pub use crate::automa::parser;
+pub use crate::automa::parser_value;
+pub use crate::automa::from_str;
+pub use crate::automa::from_slice;
+pub use crate::automa::from_reader;
 pub use crate::automa::KeyParseQueryAutoma;
-pub use crate::automa::KeyParseQueryToken;
\ No newline at end of file
+pub use crate::automa::KeyParseQueryToken;
+pub use crate::automa::PathError;
+
+pub use crate::query::JsonPath;
+pub use crate::pointer::JsonPointer;
+pub use crate::patch::JsonPatch;
+pub use crate::patch::PatchOperation;
+pub use crate::patch::PatchError;
+pub use crate::patch::PatchErrorKind;
+pub use crate::merge::merge_patch;
+pub use crate::merge::merge_diff;
+pub use crate::diff::Diff;
+pub use crate::diff::Change;
+pub use crate::diff::ArrayMode;
+pub use crate::diff::diff;
+pub use crate::three_way::merge3;
+pub use crate::three_way::merge3_with;
+pub use crate::three_way::Conflict;
+pub use crate::three_way::Resolution;
+pub use crate::three_way::Resolver;
+pub use crate::three_way::MergeResult;
+pub use crate::convert::ToJson;
+pub use crate::convert::FromJson;
+
+#[cfg(feature = "derive")]
+pub use json_derive::{ToJson, FromJson};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::convert::{expect_object, expect_list, field_from_json, element_from_json, missing_field, flatten_into};
+}
+
+#[cfg(feature = "serde")]
+pub use crate::de::{SerdeDeserializer, deserialize_str, deserialize_reader};
+#[cfg(feature = "serde")]
+pub use crate::ser::{SerdeSerializer, Compound, serialize_to_string, serialize_to_writer};
+pub use crate::automa::ParserError;
+pub use crate::automa::ParserErrorKind;
+pub use crate::automa::Position;
+
+pub use crate::pull::PullParser;
+pub use crate::pull::JsonEvent;
+pub use crate::decoder::Utf8Decoder;
+
+pub use crate::visitor::Visitor;
+pub use crate::visitor::Visit;
+pub use crate::visitor::visit;
+pub use crate::visitor::visit_reader;
+
+pub use crate::pretty::PrettyPrinter;
+pub use crate::pretty::Indent;
+pub use crate::pretty::NewLine;
+
+pub use crate::serializer::Serializer;
+pub use crate::serializer::to_writer;
+pub use crate::serializer::to_writer_pretty;
+
+pub use crate::lines::JsonLinesReader;
+pub use crate::lines::JsonLinesWriter;
+
+pub use crate::stream::JsonStream;
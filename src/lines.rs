@@ -0,0 +1,151 @@
+use std::io;
+
+use crate::automa::from_slice;
+use crate::error::Error;
+use crate::objects::TypeJson;
+use crate::serializer::to_writer;
+
+pub struct JsonLinesReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    line: usize,
+    strict: bool,
+    finished: bool,
+}
+
+impl <R: io::BufRead> JsonLinesReader<R> {
+    pub fn new(reader: R) -> JsonLinesReader<R> {
+        JsonLinesReader {
+            reader,
+            buffer: Vec::new(),
+            line: 0,
+            strict: false,
+            finished: false,
+        }
+    }
+
+    pub fn strict(mut self, strict: bool) -> JsonLinesReader<R> {
+        self.strict = strict;
+        self
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    fn fail(&mut self, error: Error) -> Option<Result<TypeJson, Error>> {
+        if self.strict {
+            self.finished = true;
+        }
+        Some(Err(Error::Line {
+            line: self.line,
+            error: Box::new(error),
+        }))
+    }
+}
+
+impl <R: io::BufRead> Iterator for JsonLinesReader<R> {
+    type Item = Result<TypeJson, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            self.buffer.clear();
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => self.finished = true,
+                Ok(_) => {
+                    self.line += 1;
+                    let line = match self.buffer.strip_suffix(b"\n") {
+                        Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+                        None => &self.buffer[..],
+                    };
+                    if line.iter().all(|byte| b" \t\r".contains(byte)) {
+                        continue;
+                    }
+                    return match from_slice(line) {
+                        Ok(value) => Some(Ok(value)),
+                        Err(error) => self.fail(error),
+                    };
+                },
+                Err(error) => {
+                    self.line += 1;
+                    self.finished = true;
+                    return self.fail(error.into());
+                },
+            }
+        }
+        None
+    }
+}
+
+pub struct JsonLinesWriter<W> {
+    writer: W,
+}
+
+impl <W: io::Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> JsonLinesWriter<W> {
+        JsonLinesWriter {
+            writer,
+        }
+    }
+
+    pub fn write(&mut self, json: &TypeJson) -> Result<(), Error> {
+        to_writer(&mut self.writer, json)?;
+        Ok(self.writer.write_all(b"\n")?)
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::lines::*;
+    use crate::objects::*;
+
+    const INPUT: &str = "{\"id\": 1}\r\n\n[2, \"two\"]\n{\"id\": \n  \n\"three\"\n";
+
+    #[test]
+    fn read_lines() {
+        let mut reader = JsonLinesReader::new(INPUT.as_bytes());
+        assert_eq!("{\"id\":1}", reader.next().unwrap().unwrap().to_string());
+        assert_eq!("[2,\"two\"]", reader.next().unwrap().unwrap().to_string());
+        match reader.next() {
+            Some(Err(Error::Line { line, error })) => {
+                assert_eq!(4, line);
+                assert!(matches!(*error, Error::Parser(_)));
+            },
+            _ => assert!(false),
+        }
+        assert_eq!("\"three\"", reader.next().unwrap().unwrap().to_string());
+        assert!(reader.next().is_none());
+        assert_eq!(6, reader.line());
+
+        let mut reader = JsonLinesReader::new(INPUT.as_bytes()).strict(true);
+        assert_eq!(2, reader.by_ref().filter(|value| value.is_ok()).count());
+        assert!(reader.next().is_none());
+
+        let error = JsonLinesReader::new(&b"1\n\xff\n"[..]).nth(1).unwrap().err().unwrap();
+        assert_eq!("line 2: invalid UTF-8 sequence at line 1, column 1 (byte 0)", error.to_string());
+    }
+
+    #[test]
+    fn write_lines() {
+        let mut writer = JsonLinesWriter::new(Vec::new());
+        let mut obj = object();
+        obj.set("text", "a\nb");
+        writer.write(&obj.into()).unwrap();
+        writer.write(&TypeJson::from(2)).unwrap();
+        writer.flush().unwrap();
+        let out = writer.into_inner();
+        assert_eq!("{\"text\":\"a\\nb\"}\n2\n", String::from_utf8(out.clone()).unwrap());
+
+        let values = JsonLinesReader::new(&out[..]).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(Some("a\nb"), values[0].as_object().unwrap().get("text").unwrap().as_text());
+    }
+}
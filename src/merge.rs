@@ -0,0 +1,149 @@
+use crate::error::Error;
+use crate::objects::{object, ObjectJson, TypeJson};
+use crate::pointer::JsonPointer;
+
+pub fn merge_patch(target: &mut TypeJson, patch: &TypeJson) {
+    match patch {
+        TypeJson::Object(patch) => {
+            if target.as_object().is_none() {
+                *target = object().into();
+            }
+            if let Some(target) = target.as_object_mut() {
+                merge_object(target, patch);
+            }
+        },
+        patch => *target = patch.clone(),
+    }
+}
+
+pub(crate) fn merge_object(target: &mut ObjectJson, patch: &ObjectJson) {
+    for (key, value) in patch.iter() {
+        if value.is_null() {
+            target.remove(key);
+            continue;
+        }
+        match target.get_mut(key) {
+            Some(node) => merge_patch(node, value),
+            None => {
+                let mut node = TypeJson::Null;
+                merge_patch(&mut node, value);
+                target.set(key, node);
+            },
+        }
+    }
+}
+
+pub fn merge_diff(from: &TypeJson, to: &TypeJson) -> Result<TypeJson, Error> {
+    diff_values(&JsonPointer::new(), from, to)
+}
+
+fn diff_values(path: &JsonPointer, from: &TypeJson, to: &TypeJson) -> Result<TypeJson, Error> {
+    let (from, to) = match (from, to) {
+        (TypeJson::Object(from), TypeJson::Object(to)) => (from, to),
+        (_, to) => {
+            check_nulls(path, to)?;
+            return Ok(to.clone());
+        },
+    };
+    let mut patch = object();
+    for (key, _) in from.iter() {
+        if to.get(key).is_none() {
+            patch.set(key, TypeJson::Null);
+        }
+    }
+    for (key, value) in to.iter() {
+        let mut child = path.clone();
+        child.push(key.as_str());
+        match from.get(key) {
+            Some(old) if old == value => {},
+            _ if value.is_null() => return Err(Error::MergeNull { path: child }),
+            Some(old) => patch.set(key, diff_values(&child, old, value)?),
+            None => patch.set(key, diff_values(&child, &TypeJson::Null, value)?),
+        }
+    }
+    Ok(patch.into())
+}
+
+fn check_nulls(path: &JsonPointer, value: &TypeJson) -> Result<(), Error> {
+    if let TypeJson::Object(obj) = value {
+        for (key, value) in obj.iter() {
+            let mut child = path.clone();
+            child.push(key.as_str());
+            if value.is_null() {
+                return Err(Error::MergeNull { path: child });
+            }
+            check_nulls(&child, value)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::merge::*;
+
+    fn json(text: &str) -> TypeJson {
+        crate::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn merge_apply() {
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+        for (target, patch, expected) in cases {
+            let mut target = json(target);
+            target.merge_patch(&json(patch));
+            assert_eq!(expected, target.to_string());
+        }
+
+        let mut obj = object();
+        obj.set("keep", true);
+        obj.set("drop", 1.5);
+        obj.merge_patch(json(r#"{"drop": null, "add": [null]}"#).as_object().unwrap());
+        assert_eq!("{\"keep\":true,\"add\":[null]}", TypeJson::from(obj).to_string());
+    }
+
+    #[test]
+    fn merge_generate() {
+        let pairs = [
+            (r#"{"a": 1, "b": {"c": "x", "d": [1]}, "e": "gone"}"#, r#"{"a": 1, "b": {"c": "y", "d": [1]}, "f": {"g": [null]}}"#),
+            (r#"{"a": {"b": 1}}"#, r#"{"a": [1]}"#),
+            ("[1]", r#"{"a": {"b": 2}}"#),
+            (r#"{"a": 1}"#, "null"),
+        ];
+        for (from, to) in pairs {
+            let (from, to) = (json(from), json(to));
+            let patch = merge_diff(&from, &to).unwrap();
+            let mut target = from.clone();
+            target.merge_patch(&patch);
+            assert!(target == to);
+        }
+        assert_eq!(r#"{"e":null,"b":{"c":"y"},"f":{"g":[null]}}"#, merge_diff(&json(pairs[0].0), &json(pairs[0].1)).unwrap().to_string());
+        assert_eq!("{}", merge_diff(&json(r#"{"n": 1}"#), &json(r#"{"n": 1.0}"#)).unwrap().to_string());
+
+        match merge_diff(&json(r#"{"a": {"b": 1}}"#), &json(r#"{"a": {"b": null}}"#)) {
+            Err(Error::MergeNull { path }) => assert_eq!("/a/b", path.to_string()),
+            _ => assert!(false),
+        }
+        match merge_diff(&json("[]"), &json(r#"{"x": {"y": null}}"#)) {
+            Err(error) => assert_eq!("merge patch cannot set \"/x/y\" to null", error.to_string()),
+            _ => assert!(false),
+        }
+    }
+}
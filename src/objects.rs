@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
+use crate::error::Error;
+
 const NULL_JSON: TypeJson = TypeJson::Null;
 
+#[derive(Clone)]
 pub enum TypeJson {
     Object(ObjectJson),
     List(ListJson),
@@ -79,79 +82,264 @@ impl TypeJson {
         }
     }
 
-    pub fn traverse(self, path: &str) -> Result<TypeJson, String> {
-        let mut ret = self.into();
-        let mut chars = path.chars();
-        let mut automa = crate::automa::KeyParseQueryAutoma::new(&mut chars);
-        while let Some(token) = automa.next() {
-            match token {
-                crate::automa::KeyParseQueryToken::Key(key) => ret = match ret {
-                    TypeJson::Object(mut obj) => obj.remove(&key).ok_or("Unable to traverse")?,
-                    _ => TypeJson::Null, 
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TypeJson::Object(_) => "object",
+            TypeJson::List(_) => "array",
+            TypeJson::Text(_) => "string",
+            TypeJson::Number(_) => "number",
+            TypeJson::Boolean(_) => "boolean",
+            TypeJson::Null => "null",
+        }
+    }
+
+    pub fn pointer(&self, pointer: &str) -> Option<&TypeJson> {
+        crate::pointer::JsonPointer::parse(pointer).ok()?.resolve(self)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut TypeJson> {
+        crate::pointer::JsonPointer::parse(pointer).ok()?.resolve_mut(self)
+    }
+
+    pub fn merge_patch(&mut self, patch: &TypeJson) {
+        crate::merge::merge_patch(self, patch)
+    }
+
+    pub fn path_mut(&mut self, path: &str) -> Option<&mut TypeJson> {
+        let mut node = self;
+        for token in path_tokens(path).ok()? {
+            node = match (node, token) {
+                (TypeJson::Object(obj), crate::automa::KeyParseQueryToken::Key(key)) => obj.get_mut(&key)?,
+                (TypeJson::List(list), crate::automa::KeyParseQueryToken::Index(i)) => list.get_mut(i)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    pub fn set_path(&mut self, path: &str, value: impl Into<TypeJson>) -> Result<(), Error> {
+        self.set_path_with(path, value.into(), false)
+    }
+
+    pub fn set_path_padded(&mut self, path: &str, value: impl Into<TypeJson>) -> Result<(), Error> {
+        self.set_path_with(path, value.into(), true)
+    }
+
+    fn set_path_with(&mut self, path: &str, value: TypeJson, pad: bool) -> Result<(), Error> {
+        let tokens = path_tokens(path)?;
+        self.set_tokens(path, &tokens, String::new(), value, pad)
+    }
+
+    fn set_tokens(&mut self, path: &str, tokens: &[crate::automa::KeyParseQueryToken], mut resolved: String, value: TypeJson, pad: bool) -> Result<(), Error> {
+        let mut node = self;
+        for (i, token) in tokens.iter().enumerate() {
+            let fail = |resolved: &str, reason| LookupError {
+                path: String::from(path),
+                resolved: String::from(resolved),
+                segment: token.clone(),
+                reason,
+            };
+            let missing = || {
+                let mut missing = match tokens.get(i + 1) {
+                    Some(crate::automa::KeyParseQueryToken::Index(_)) => TypeJson::List(ListJson::new()),
+                    Some(_) => TypeJson::Object(ObjectJson::new()),
+                    None => TypeJson::Null,
+                };
+                let resolved = resolved.clone() + &token.to_string();
+                missing.set_tokens(path, &tokens[i + 1..], resolved, value.clone(), pad).map(|_| missing)
+            };
+            node = match (node, token) {
+                (TypeJson::Object(obj), crate::automa::KeyParseQueryToken::Key(key)) => {
+                    if obj.get(key).is_none() {
+                        obj.set(key, missing()?);
+                        return Ok(());
+                    }
+                    obj.get_mut(key).unwrap()
                 },
-                crate::automa::KeyParseQueryToken::Index(i) => ret = match ret {
-                    TypeJson::List(mut list) => list.remove(i),
-                    _ => TypeJson::Null, 
+                (TypeJson::List(list), &crate::automa::KeyParseQueryToken::Index(index)) => {
+                    if index > list.len() && !pad {
+                        return Err(fail(&resolved, LookupReason::IndexOutOfBounds { len: list.len() }).into());
+                    }
+                    if index >= list.len() {
+                        let missing = missing()?;
+                        while list.len() < index {
+                            list.add(TypeJson::Null);
+                        }
+                        list.add(missing);
+                        return Ok(());
+                    }
+                    list.get_mut(index).unwrap()
                 },
-                crate::automa::KeyParseQueryToken::Error(msg) => return Err(msg),
+                (node, token) => return Err(fail(&resolved, LookupReason::wrong_type(node, token)).into()),
+            };
+            resolved.push_str(&token.to_string());
+        }
+        *node = value;
+        Ok(())
+    }
+
+    pub fn remove_path(&mut self, path: &str) -> Option<TypeJson> {
+        let mut tokens = path_tokens(path).ok()?;
+        let last = tokens.pop()?;
+        let mut parent = self;
+        for token in tokens {
+            parent = match (parent, token) {
+                (TypeJson::Object(obj), crate::automa::KeyParseQueryToken::Key(key)) => obj.get_mut(&key)?,
+                (TypeJson::List(list), crate::automa::KeyParseQueryToken::Index(i)) => list.get_mut(i)?,
+                _ => return None,
+            };
+        }
+        match (parent, last) {
+            (TypeJson::Object(obj), crate::automa::KeyParseQueryToken::Key(key)) => obj.remove(&key),
+            (TypeJson::List(list), crate::automa::KeyParseQueryToken::Index(i)) if i < list.len() => Some(list.remove(i)),
+            _ => None,
+        }
+    }
+
+    pub fn lookup(&self, path: &str) -> Result<&TypeJson, Error> {
+        let mut resolved = String::new();
+        let mut node = self;
+        for token in path_tokens(path)? {
+            let next = match (node, &token) {
+                (TypeJson::Object(obj), crate::automa::KeyParseQueryToken::Key(key)) => obj.get(key).ok_or(LookupReason::MissingKey),
+                (TypeJson::List(list), &crate::automa::KeyParseQueryToken::Index(i)) => list.get(i).ok_or(LookupReason::IndexOutOfBounds { len: list.len() }),
+                (node, token) => Err(LookupReason::wrong_type(node, token)),
+            };
+            node = match next {
+                Ok(next) => next,
+                Err(reason) => return Err(LookupError {
+                    path: String::from(path),
+                    resolved,
+                    segment: token,
+                    reason,
+                }.into()),
             };
+            resolved.push_str(&token.to_string());
+        }
+        Ok(node)
+    }
+
+    pub fn traverse(mut self, path: &str) -> Result<TypeJson, Error> {
+        self.lookup(path)?;
+        Ok(self.remove_path(path).unwrap_or(self))
+    }
+}
+
+impl PartialEq for TypeJson {
+    fn eq(&self, other: &TypeJson) -> bool {
+        match (self, other) {
+            (TypeJson::Object(left), TypeJson::Object(right)) => left == right,
+            (TypeJson::List(left), TypeJson::List(right)) => left == right,
+            (TypeJson::Text(left), TypeJson::Text(right)) => left == right,
+            (TypeJson::Number(left), TypeJson::Number(right)) => left == right,
+            (TypeJson::Boolean(left), TypeJson::Boolean(right)) => left == right,
+            (TypeJson::Null, TypeJson::Null) => true,
+            _ => false,
+        }
+    }
+}
+
+fn path_tokens(path: &str) -> Result<Vec<crate::automa::KeyParseQueryToken>, Error> {
+    let mut chars = path.chars();
+    crate::automa::KeyParseQueryAutoma::new(&mut chars)
+        .map(|token| match token {
+            crate::automa::KeyParseQueryToken::Error(error) => Err(error.with_path(path).into()),
+            token => Ok(token),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupReason {
+    MissingKey,
+    IndexOutOfBounds {
+        len: usize,
+    },
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl LookupReason {
+    fn wrong_type(node: &TypeJson, token: &crate::automa::KeyParseQueryToken) -> LookupReason {
+        LookupReason::WrongType {
+            expected: match token {
+                crate::automa::KeyParseQueryToken::Index(_) => "array",
+                _ => "object",
+            },
+            found: node.type_name(),
         }
-        Ok(ret)
     }
 }
 
-impl ToString for TypeJson {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for LookupReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TypeJson::Object(object) => object.to_string(),
-            TypeJson::List(list) => list.to_string(),
-            TypeJson::Text(txt) => string_to_json_escape(txt),
-            TypeJson::Number(num) => num.to_string(),
-            TypeJson::Boolean(b) => b.to_string(),
-            TypeJson::Null => String::from("null"),
-        }
-    }
-}
-
-fn string_to_json_escape(txt: &str) -> String {
-    return std::iter::once('"')
-        .chain(txt
-            .chars()
-            .flat_map(|c| {
-                let iter: Box<dyn Iterator<Item = char>>;
-                match c {
-                    '"' => iter = to_iter("\\\""),
-                    '\n' => iter = to_iter("\\n"),
-                    '\r' => iter = to_iter("\\r"),
-                    '\t' => iter = to_iter("\\t"),
-                    other => iter = Box::new(std::iter::once(other)),
-                }
-                iter
-            })
-        )
-        .chain(std::iter::once('"'))
-        .collect();
-    
-    fn to_iter(txt: &'static str) -> Box<dyn Iterator<Item = char>> {
-        Box::new(txt.chars())
+            LookupReason::MissingKey => write!(f, "missing key"),
+            LookupReason::IndexOutOfBounds { len } => write!(f, "index out of bounds for array of length {}", len),
+            LookupReason::WrongType { expected, found } => write!(f, "expected {}, found {}", expected, found),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupError {
+    path: String,
+    resolved: String,
+    segment: crate::automa::KeyParseQueryToken,
+    reason: LookupReason,
+}
+
+impl LookupError {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn resolved(&self) -> &str {
+        &self.resolved
+    }
+
+    pub fn segment(&self) -> &crate::automa::KeyParseQueryToken {
+        &self.segment
+    }
+
+    pub fn reason(&self) -> &LookupReason {
+        &self.reason
+    }
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot resolve {} after {:?} in {:?}: {}", self.segment, self.resolved, self.path, self.reason)
     }
 }
 
+impl std::error::Error for LookupError {}
+
+#[derive(Clone)]
 pub struct NumberExponent {
-    number: i32,
+    number: i64,
 }
 
 impl NumberExponent {
     pub fn new(value: i32) -> NumberExponent {
+        NumberExponent {
+            number: value as i64,
+        }
+    }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    pub(crate) fn wide(value: i64) -> NumberExponent {
         NumberExponent {
             number: value,
         }
     }
 }
 
-impl ToString for NumberExponent {
-    fn to_string(&self) -> String {
-        format!("e{}", self.number.to_string())
+impl std::fmt::Display for NumberExponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "e{}", self.number)
     }
 }
 
@@ -165,30 +353,168 @@ impl From<NumberExponent> for f32 {
 impl From<&NumberExponent> for f32 {
     
     fn from(value: &NumberExponent) -> Self {
-        10_f32.powi(value.number)
+        10_f32.powi(value.number.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
     }
 }
 
+const MAX_SAFE_INTEGER: u128 = 1 << 53;
+
+#[derive(Clone)]
+enum NumberValue {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+}
+
+#[derive(Clone)]
 pub struct Number {
-    number: f32,
+    number: NumberValue,
     exponent: Option<NumberExponent>,
 }
 
 impl Number {
-    pub fn new(value: f32, exponent: Option<NumberExponent>) -> Self {
+    pub fn new(value: f64, exponent: Option<NumberExponent>) -> Self {
         Number {
-            number: value,
+            number: NumberValue::Float(value),
             exponent,
         }
     }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    pub(crate) fn from_decimal(mantissa: &str, exponent: Option<NumberExponent>) -> Option<Self> {
+        let number = if mantissa.contains('.') || mantissa == "-0" {
+            NumberValue::Float(mantissa.parse().ok()?)
+        } else if let Ok(number) = mantissa.parse() {
+            NumberValue::Unsigned(number)
+        } else if let Ok(number) = mantissa.parse() {
+            NumberValue::Signed(number)
+        } else {
+            let exponent = exponent.map(|exp| exp.to_string()).unwrap_or_default();
+            return Some(Number::verbatim(format!("{}{}", mantissa, exponent)));
+        };
+        match number {
+            NumberValue::Float(number) if !number.is_finite() => None,
+            number => Some(Number {
+                number,
+                exponent,
+            }),
+        }
+    }
+
+    pub(crate) fn verbatim(text: String) -> Self {
+        Number {
+            number: NumberValue::Text(text),
+            exponent: None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.integer().and_then(|number| i64::try_from(number).ok())
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.integer().and_then(|number| u64::try_from(number).ok())
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        let number = self.float()?;
+        match self.integer() {
+            Some(integer) if number as i128 != integer => None,
+            _ => Some(number),
+        }
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        match &self.number {
+            NumberValue::Unsigned(_) | NumberValue::Signed(_) => true,
+            NumberValue::Float(_) => false,
+            NumberValue::Text(text) => !text.contains(['.', 'e', 'E']) && text != "-0",
+        }
+    }
+
+    pub(crate) fn compare(&self, other: &Number) -> Option<std::cmp::Ordering> {
+        match (self.integer(), other.integer()) {
+            (Some(left), Some(right)) => Some(left.cmp(&right)),
+            _ => self.float()?.partial_cmp(&other.float()?),
+        }
+    }
+
+    fn float(&self) -> Option<f64> {
+        self.to_string()
+            .parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite())
+    }
+
+    fn integer(&self) -> Option<i128> {
+        let mantissa = match &self.number {
+            NumberValue::Unsigned(number) => *number as i128,
+            NumberValue::Signed(number) => *number as i128,
+            NumberValue::Float(_) => return self.float().and_then(safe_integer),
+            NumberValue::Text(text) => return decimal_integer(text),
+        };
+        scale_integer(mantissa, self.exponent.as_ref().map(|exp| exp.number).unwrap_or(0))
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match self.compare(other) {
+            Some(ordering) => ordering == std::cmp::Ordering::Equal,
+            None => self.to_string() == other.to_string(),
+        }
+    }
+}
+
+fn scale_integer(mantissa: i128, exponent: i64) -> Option<i128> {
+    if mantissa == 0 {
+        return Some(0);
+    }
+    let scale = 10_i128.checked_pow(u32::try_from(exponent.unsigned_abs()).ok()?)?;
+    if exponent >= 0 {
+        mantissa.checked_mul(scale)
+    } else if mantissa % scale == 0 {
+        Some(mantissa / scale)
+    } else {
+        None
+    }
+}
+
+fn decimal_integer(text: &str) -> Option<i128> {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], text[i + 1..].parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let digits = format!("{integer}{fraction}").parse().ok()?;
+    scale_integer(digits, exponent.checked_sub(fraction.len() as i64)?)
+}
+
+fn safe_integer(number: f64) -> Option<i128> {
+    if number.fract() == 0.0 && number.abs() as u128 <= MAX_SAFE_INTEGER {
+        Some(number as i128)
+    } else {
+        None
+    }
 }
 
-impl ToString for Number {
-    fn to_string(&self) -> String {
-        format!("{}{}", self.number.to_string(), self.exponent
-            .as_ref()
-            .map(|exp| exp.to_string())
-            .unwrap_or("".to_string()))
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.number {
+            NumberValue::Unsigned(number) => write!(f, "{}", number)?,
+            NumberValue::Signed(number) => write!(f, "{}", number)?,
+            NumberValue::Float(number) if !number.is_finite() => return f.write_str("null"),
+            NumberValue::Float(number) if *number == 0.0 && number.is_sign_negative() => f.write_str("-0")?,
+            NumberValue::Float(number) if number.fract() == 0.0 => write!(f, "{}.0", number)?,
+            NumberValue::Float(number) => write!(f, "{}", number)?,
+            NumberValue::Text(text) => f.write_str(text)?,
+        }
+        match &self.exponent {
+            Some(exp) => write!(f, "{}", exp),
+            None => Ok(()),
+        }
     }
 }
 
@@ -202,36 +528,84 @@ impl From<Number> for f32 {
 impl From<&Number> for f32 {
     
     fn from(value: &Number) -> Self {
-        value.number as f32 * value.exponent.as_ref().map(|exp| exp.into()).unwrap_or(1.0)
+        value.to_string().parse().unwrap_or(f32::NAN)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::new(value, None)
     }
 }
 
 impl From<f32> for Number {
     fn from(value: f32) -> Self {
-        Number::new(value, None)
+        // Going through the shortest f32 representation keeps 12.1_f32 as 12.1 instead of 12.100000381469727.
+        Number::new(value.to_string().parse().unwrap_or(value as f64), None)
     }
 }
 
+macro_rules! number_from_integer {
+    ($variant:ident, $target:ty, $($source:ty),*) => {
+        $(
+            impl From<$source> for Number {
+                fn from(value: $source) -> Self {
+                    Number {
+                        number: NumberValue::$variant(value as $target),
+                        exponent: None,
+                    }
+                }
+            }
+
+            impl From<$source> for TypeJson {
+                fn from(value: $source) -> Self {
+                    Number::from(value).into()
+                }
+            }
+        )*
+    };
+}
+
+number_from_integer!(Signed, i64, i8, i16, i32, i64, isize);
+number_from_integer!(Unsigned, u64, u8, u16, u32, u64, usize);
+
 impl From<Number> for TypeJson {
     fn from(value: Number) -> Self {
         TypeJson::Number(value)
     }
 }
 
+#[derive(Clone)]
 pub struct ObjectJson {
-    parameters: HashMap<String, TypeJson>,
+    parameters: Vec<(String, TypeJson)>,
+    index: HashMap<String, usize>,
 }
 
 impl ObjectJson {
 
     fn new() -> ObjectJson {
         ObjectJson {
-            parameters: HashMap::new(),
+            parameters: Vec::new(),
+            index: HashMap::new(),
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
     pub fn set(&mut self, key: &str, obj: impl Into<TypeJson>) {
-        self.parameters.insert(String::from(key), obj.into());
+        match self.index.get(key) {
+            Some(&i) => self.parameters[i].1 = obj.into(),
+            None => {
+                self.index.insert(String::from(key), self.parameters.len());
+                self.parameters.push((String::from(key), obj.into()));
+            },
+        }
     }
 
     pub fn object(&mut self, key: &str) -> &mut ObjectJson {
@@ -251,25 +625,38 @@ impl ObjectJson {
     }
 
     pub fn get(&self, key: &str) -> Option<&TypeJson> {
-        self.parameters
+        self.index
             .get(key)
+            .map(|&i| &self.parameters[i].1)
     }
 
     pub fn get_mut(&mut self, key: &str) -> Option<&mut TypeJson> {
-        self.parameters
-            .get_mut(key)
+        self.index
+            .get(key)
+            .map(|&i| &mut self.parameters[i].1)
     }
 
     pub fn remove(&mut self, key: &str) -> Option<TypeJson> {
-        self.parameters.remove(key)
+        let i = self.index.remove(key)?;
+        let (_, obj) = self.parameters.remove(i);
+        for (key, _) in &self.parameters[i..] {
+            if let Some(position) = self.index.get_mut(key) {
+                *position -= 1;
+            }
+        }
+        Some(obj)
+    }
+
+    pub fn merge_patch(&mut self, patch: &ObjectJson) {
+        crate::merge::merge_object(self, patch)
     }
 
     pub fn iter(&self) -> impl Iterator<Item=(&String, &TypeJson)> {
-        self.parameters.iter()
+        self.parameters.iter().map(|(key, obj)| (key, obj))
     }
 
     pub fn iter_mut(&mut self) -> impl Iterator<Item=(&String, &mut TypeJson)> {
-        self.parameters.iter_mut()
+        self.parameters.iter_mut().map(|(key, obj)| (&*key, obj))
     }
 
     pub fn keys(&self) -> impl Iterator<Item=&String> {
@@ -277,27 +664,20 @@ impl ObjectJson {
     }
 }
 
-impl ToString for ObjectJson {
-    fn to_string(&self) -> String {
-        std::iter::once('{')
-        .chain(self
-            .iter()
-            .map(|(key, obj)| format!("\"{}\":{}", key, obj.to_string()))
-            .collect::<Vec<_>>()
-            .join(",")
-            .chars()
-        )
-        .chain(std::iter::once('}'))
-        .collect()
-    }
-}
-
 impl From<ObjectJson> for TypeJson {
     fn from(object: ObjectJson) -> TypeJson {
         TypeJson::Object(object)
     }
 }
 
+impl PartialEq for ObjectJson {
+    fn eq(&self, other: &ObjectJson) -> bool {
+        self.len() == other.len()
+            && self.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct ListJson {
     list: Vec<TypeJson>,
 }
@@ -317,6 +697,10 @@ impl ListJson {
         self.list.push(obj.into());
     }
 
+    pub fn insert(&mut self, index: usize, obj: impl Into<TypeJson>) {
+        self.list.insert(index, obj.into());
+    }
+
     pub fn pop(&mut self) -> Option<TypeJson> {
         self.list.pop()
     }
@@ -385,21 +769,6 @@ impl <'a> std::iter::IntoIterator for &'a mut ListJson {
     }
 }
 
-impl ToString for ListJson {
-    fn to_string(&self)  -> String {
-        std::iter::once('[')
-            .chain(self
-                .iter()
-                .map(|obj|obj.to_string())
-                .collect::<Vec<_>>()
-                .join(",")
-                .chars()
-            )
-            .chain(std::iter::once(']'))
-            .collect()
-    }
-}
-
 impl From<ListJson> for TypeJson {
     fn from(list: ListJson) -> TypeJson {
         TypeJson::List(list)
@@ -424,12 +793,47 @@ impl From<f32> for TypeJson {
     }
 }
 
+impl From<f64> for TypeJson {
+    fn from(value: f64) -> Self {
+        Number::from(value).into()
+    }
+}
+
 impl From<bool> for TypeJson {
     fn from(value: bool) -> Self {
         TypeJson::Boolean(value)
     }
 }
 
+impl <'a> TryFrom<&'a TypeJson> for &'a str {
+    type Error = Error;
+    fn try_from(value: &'a TypeJson) -> Result<Self, Self::Error> {
+        value.as_text().ok_or(Error::Conversion { expected: "string", found: value.type_name() })
+    }
+}
+
+impl TryFrom<&TypeJson> for bool {
+    type Error = Error;
+    fn try_from(value: &TypeJson) -> Result<Self, Self::Error> {
+        value.as_bool().copied().ok_or(Error::Conversion { expected: "boolean", found: value.type_name() })
+    }
+}
+
+macro_rules! number_try_from_json {
+    ($($t:ty => $accessor:ident),*) => {
+        $(
+            impl TryFrom<&TypeJson> for $t {
+                type Error = Error;
+                fn try_from(value: &TypeJson) -> Result<Self, Self::Error> {
+                    value.as_number().and_then(|number| number.$accessor()).ok_or(Error::Conversion { expected: stringify!($t), found: value.type_name() })
+                }
+            }
+        )*
+    };
+}
+
+number_try_from_json!(i64 => as_i64, u64 => as_u64, f64 => as_f64);
+
 pub struct NullJson;
 
 impl NullJson {
@@ -501,16 +905,16 @@ impl <'a> ReaderJson<'a> {
         }
     }
     
-    pub fn path_check(&self, path: &str) -> Result<ReaderJson<'a>, String> {
+    pub fn path_check(&self, path: &str) -> Result<ReaderJson<'a>, Error> {
         if let Some(root) = self.root {
             let mut ret = ReaderJson::new(root);
-            let mut path = path.chars();
-            let mut automa = crate::automa::KeyParseQueryAutoma::new(&mut path);
+            let mut chars = path.chars();
+            let mut automa = crate::automa::KeyParseQueryAutoma::new(&mut chars);
             while let Some(token) = automa.next() {
                 match token {
                     crate::automa::KeyParseQueryToken::Key(key) => ret = ret.field(&key),
                     crate::automa::KeyParseQueryToken::Index(i) => ret = ret.index(i),
-                    crate::automa::KeyParseQueryToken::Error(msg) => return Err(msg),
+                    crate::automa::KeyParseQueryToken::Error(error) => return Err(error.with_path(path).into()),
                 };
             }
             Ok(ret)
@@ -520,12 +924,96 @@ impl <'a> ReaderJson<'a> {
         
     }
 
+    pub fn query(&self, query: &str) -> Vec<ReaderJson<'a>> {
+        self.query_check(query).unwrap_or_default()
+    }
+
+    pub fn query_check(&self, query: &str) -> Result<Vec<ReaderJson<'a>>, Error> {
+        let query = crate::query::JsonPath::parse(query)?;
+        match self.root {
+            Some(root) => Ok(query.select(root).into_iter().map(ReaderJson::new).collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
     pub fn json(&self) -> &'a TypeJson {
         match self.root {
             Some(node) => node,
             _ => &NULL_JSON,
         }
     }
+
+    pub fn exists(&self) -> bool {
+        self.root.is_some()
+    }
+
+    pub fn is_missing(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.root, Some(TypeJson::Null))
+    }
+
+    pub fn text(&self) -> Option<&'a str> {
+        self.root?.as_text()
+    }
+
+    pub fn i64(&self) -> Option<i64> {
+        self.root?.as_number()?.as_i64()
+    }
+
+    pub fn u64(&self) -> Option<u64> {
+        self.root?.as_number()?.as_u64()
+    }
+
+    pub fn f64(&self) -> Option<f64> {
+        self.root?.as_number()?.as_f64()
+    }
+
+    pub fn bool(&self) -> Option<bool> {
+        self.root?.as_bool().copied()
+    }
+
+    pub fn text_or(&self, default: &'a str) -> &'a str {
+        self.text().unwrap_or(default)
+    }
+
+    pub fn i64_or(&self, default: i64) -> i64 {
+        self.i64().unwrap_or(default)
+    }
+
+    pub fn u64_or(&self, default: u64) -> u64 {
+        self.u64().unwrap_or(default)
+    }
+
+    pub fn f64_or(&self, default: f64) -> f64 {
+        self.f64().unwrap_or(default)
+    }
+
+    pub fn bool_or(&self, default: bool) -> bool {
+        self.bool().unwrap_or(default)
+    }
+
+    pub fn array_len(&self) -> Option<usize> {
+        self.root?.as_list().map(|list| list.len())
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item=(&'a str, ReaderJson<'a>)> {
+        self.root
+            .and_then(|node| node.as_object())
+            .into_iter()
+            .flat_map(|obj| obj.iter())
+            .map(|(key, node)| (key.as_str(), ReaderJson::new(node)))
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item=ReaderJson<'a>> {
+        self.root
+            .and_then(|node| node.as_list())
+            .into_iter()
+            .flat_map(|list| list.iter())
+            .map(ReaderJson::new)
+    }
 }
 
 
@@ -638,6 +1126,55 @@ mod tests {
         assert_eq!(Some("message-2"), reader.field("k2").field("k3").field("n6").index(1).json().as_text());
 
         assert_eq!(Some("message-2"), reader.path(".k2.k3.n6[1]").json().as_text());
+
+        let found = reader.query("$..n6[?@.k1 == 'v1'].k1");
+        assert_eq!(1, found.len());
+        assert_eq!(Some("v1"), found[0].json().as_text());
+        assert_eq!(2, reader.query(".k2.k3.n6[-1:-3:-1]").len());
+        assert!(reader.query("$[").is_empty());
+        assert!(reader.query_check("$[").is_err());
+    }
+
+    #[test]
+    fn reader_getters() {
+        let root = crate::parser_value(r#"{"name": "x", "n": 42, "neg": -3, "f": 1.5, "ok": true, "none": null, "list": [1, "two", [3]]}"#.chars()).unwrap();
+        let reader = ReaderJson::new(&root);
+        assert!(reader.field("none").exists());
+        assert!(reader.field("none").is_null());
+        assert!(!reader.field("none").is_missing());
+        assert!(reader.field("other").is_missing());
+        assert!(!reader.field("other").is_null());
+        assert!(reader.field("name").field("x").is_missing());
+
+        assert_eq!(Some("x"), reader.field("name").text());
+        assert_eq!(None, reader.field("n").text());
+        assert_eq!(Some(42), reader.field("n").i64());
+        assert_eq!(Some(42), reader.field("n").u64());
+        assert_eq!(None, reader.field("neg").u64());
+        assert_eq!(Some(1.5), reader.field("f").f64());
+        assert_eq!(Some(true), reader.field("ok").bool());
+        assert_eq!(None, reader.field("none").bool());
+
+        assert_eq!("x", reader.field("name").text_or("default"));
+        assert_eq!("default", reader.field("none").text_or("default"));
+        assert_eq!(7, reader.field("other").i64_or(7));
+        assert_eq!(9, reader.field("name").u64_or(9));
+        assert_eq!(0.5, reader.field("other").f64_or(0.5));
+        assert!(reader.field("other").bool_or(true));
+
+        assert_eq!(Some(3), reader.field("list").array_len());
+        assert_eq!(None, reader.field("name").array_len());
+        assert_eq!(None, reader.field("other").array_len());
+
+        let keys = reader.fields().map(|(key, _)| key).collect::<Vec<_>>();
+        assert_eq!(vec!["name", "n", "neg", "f", "ok", "none", "list"], keys);
+        assert_eq!(Some(42), reader.fields().find(|(key, _)| *key == "n").and_then(|(_, child)| child.i64()));
+        let elements = reader.field("list").elements().collect::<Vec<_>>();
+        assert_eq!(Some(1), elements[0].i64());
+        assert_eq!(Some("two"), elements[1].text());
+        assert_eq!(Some(1), elements[2].array_len());
+        assert_eq!(0, reader.field("name").elements().count());
+        assert_eq!(0, reader.field("other").fields().count());
     }
 
     #[test]
@@ -660,6 +1197,39 @@ mod tests {
             root.to_string());
     }
 
+    #[test]
+    fn escape_round_trip() {
+        let text = "quote \" slash \\ / \u{8}\u{c}\n\r\t \u{1} \u{1f} é 😀";
+        let json = TypeJson::from(text).to_string();
+        assert_eq!("\"quote \\\" slash \\\\ / \\b\\f\\n\\r\\t \\u0001 \\u001f é 😀\"", json);
+        assert_eq!(Some(text), crate::parser_value(json.chars()).unwrap().as_text());
+    }
+
+    #[test]
+    fn object_order() {
+        let mut root = object();
+        root.set("zeta", 1);
+        root.set("alpha", 2);
+        root.set("mid", 3);
+        root.set("beta", 4);
+        assert_eq!("{\"zeta\":1,\"alpha\":2,\"mid\":3,\"beta\":4}", root.to_string());
+
+        root.set("alpha", "two");
+        assert_eq!(vec!["zeta", "alpha", "mid", "beta"], root.keys().collect::<Vec<_>>());
+
+        assert_eq!(Some(3), root.remove("mid").and_then(|obj| obj.as_number().and_then(|n| n.as_i64())));
+        assert!(root.remove("mid").is_none());
+        assert_eq!(vec!["zeta", "alpha", "beta"], root.keys().collect::<Vec<_>>());
+        assert_eq!(Some("two"), root.get("alpha").unwrap().as_text());
+        assert_eq!(Some(4), root.get("beta").unwrap().as_number().unwrap().as_i64());
+
+        root.set("mid", true);
+        assert_eq!("{\"zeta\":1,\"alpha\":\"two\",\"beta\":4,\"mid\":true}", root.to_string());
+
+        let parsed = crate::parser("{\"b\": 1, \"a\": [], \"c\": {\"y\": 1, \"x\": 2}}".chars()).unwrap();
+        assert_eq!("{\"b\":1,\"a\":[],\"c\":{\"y\":1,\"x\":2}}", parsed.to_string());
+    }
+
     #[test]
     fn bool_json() {
         let mut root = object();
@@ -689,7 +1259,171 @@ mod tests {
         let array = list.list();
         array.add("v1");
 
-        assert_eq!("message-2", TypeJson::from(root).traverse(".k2.k3.n6[1]").unwrap().as_text().unwrap())
+        let root = TypeJson::from(root);
+        let reader = ReaderJson::new(&root);
+        match reader.path_check(".k2.k3[") {
+            Err(Error::Path(error)) => {
+                assert_eq!(".k2.k3[", error.path());
+                assert_eq!("invalid path \".k2.k3[\" at byte 7: unexpected end of path, expected digit", error.to_string());
+            },
+            _ => assert!(false),
+        }
+        assert_eq!(Ok("v1"), <&str>::try_from(reader.path(".k2.k3.n6[2].k1").json()).map_err(|e| e.to_string()));
+        match i64::try_from(reader.path(".k1.n1").json()) {
+            Err(Error::Conversion { expected, found }) => assert_eq!(("i64", "string"), (expected, found)),
+            _ => assert!(false),
+        }
+
+        match TypeJson::from(object()).traverse(".missing") {
+            Err(Error::Lookup(error)) => {
+                assert_eq!(".missing", error.path());
+                assert_eq!("", error.resolved());
+                assert_eq!(&LookupReason::MissingKey, error.reason());
+            },
+            _ => assert!(false),
+        }
+        assert_eq!("v1", root.lookup(".k2.k3.n6[2].k1").unwrap().as_text().unwrap());
+        match root.lookup(".k2.k3.n6[9]") {
+            Err(Error::Lookup(error)) => {
+                assert_eq!(".k2.k3.n6", error.resolved());
+                assert_eq!(&crate::automa::KeyParseQueryToken::Index(9), error.segment());
+                assert_eq!(&LookupReason::IndexOutOfBounds { len: 4 }, error.reason());
+                assert_eq!("cannot resolve [9] after \".k2.k3.n6\" in \".k2.k3.n6[9]\": index out of bounds for array of length 4", error.to_string());
+            },
+            _ => assert!(false),
+        }
+        match root.lookup(".k1.n1.x") {
+            Err(Error::Lookup(error)) => {
+                assert_eq!(".k1.n1", error.resolved());
+                assert_eq!(&LookupReason::WrongType { expected: "object", found: "string" }, error.reason());
+            },
+            _ => assert!(false),
+        }
+        match root.lookup(".k1[0]") {
+            Err(Error::Lookup(error)) => assert_eq!(&LookupReason::WrongType { expected: "array", found: "object" }, error.reason()),
+            _ => assert!(false),
+        }
+        assert!(matches!(root.lookup(".k1["), Err(Error::Path(_))));
+        assert!(root.lookup("").unwrap().as_object().is_some());
+        assert!(matches!(TypeJson::from(ListJson::new()).traverse("[3]"), Err(Error::Lookup(_))));
+        assert_eq!("message-2", root.traverse(".k2.k3.n6[1]").unwrap().as_text().unwrap())
+    }
+
+    #[test]
+    fn mutable_path() {
+        let mut root = crate::parser_value(r#"{"a": {"b": [1, 2]}, "t": "x"}"#.chars()).unwrap();
+        *root.path_mut(".a.b[1]").unwrap() = "two".into();
+        *root.pointer_mut("/a/b/0").unwrap() = true.into();
+        assert!(root.path_mut(".a.b[2]").is_none());
+        assert!(root.path_mut(".a[").is_none());
+        assert_eq!("{\"a\":{\"b\":[true,\"two\"]},\"t\":\"x\"}", root.to_string());
+
+        root.set_path(".a.b[2].c", "c").unwrap();
+        root.set_path(".x.y[0]", "new").unwrap();
+        root.set_path(".t", null()).unwrap();
+        assert_eq!("{\"a\":{\"b\":[true,\"two\",{\"c\":\"c\"}]},\"t\":null,\"x\":{\"y\":[\"new\"]}}", root.to_string());
+
+        match root.set_path(".a.b[5]", 1.0) {
+            Err(Error::Lookup(error)) => assert_eq!(&LookupReason::IndexOutOfBounds { len: 3 }, error.reason()),
+            _ => assert!(false),
+        }
+        match root.set_path(".a.b[0].c", 1.0) {
+            Err(Error::Lookup(error)) => {
+                assert_eq!(".a.b[0]", error.resolved());
+                assert_eq!(&LookupReason::WrongType { expected: "object", found: "boolean" }, error.reason());
+            },
+            _ => assert!(false),
+        }
+        assert!(matches!(root.set_path(".a..", 1.0), Err(Error::Path(_))));
+        let before = root.to_string();
+        match root.set_path(".z.q[3]", 1.0) {
+            Err(Error::Lookup(error)) => {
+                assert_eq!(".z.q", error.resolved());
+                assert_eq!(&LookupReason::IndexOutOfBounds { len: 0 }, error.reason());
+            },
+            _ => assert!(false),
+        }
+        assert!(root.set_path(".a.b[3].k[1]", true).is_err());
+        assert_eq!(before, root.to_string());
+        root.set_path_padded(".a.b[5]", false).unwrap();
+        assert_eq!("[true,\"two\",{\"c\":\"c\"},null,null,false]", root.path_mut(".a.b").unwrap().to_string());
+
+        assert_eq!("\"two\"", root.remove_path(".a.b[1]").unwrap().to_string());
+        assert_eq!("{\"y\":[\"new\"]}", root.remove_path(".x").unwrap().to_string());
+        assert!(root.remove_path(".a.b[9]").is_none());
+        assert!(root.remove_path(".missing.key").is_none());
+        assert_eq!("{\"a\":{\"b\":[true,{\"c\":\"c\"},null,null,false]},\"t\":null}", root.to_string());
+    }
+
+    #[test]
+    fn number_lossless() {
+        let parse = |input: &str| match crate::parser_value(input.chars()) {
+            Ok(TypeJson::Number(number)) => number,
+            _ => unreachable!(),
+        };
+
+        let number = parse("9007199254740993");
+        assert_eq!(Some(9007199254740993), number.as_u64());
+        assert_eq!(Some(9007199254740993), number.as_i64());
+        assert_eq!(None, number.as_f64());
+        assert_eq!("9007199254740993", number.to_string());
+        assert_eq!(Some(9007199254740994.0), parse("9007199254740994").as_f64());
+        assert_eq!(Some(-9223372036854775808.0), parse("-9223372036854775808").as_f64());
+
+        let number = parse("-9223372036854775808");
+        assert_eq!(Some(i64::MIN), number.as_i64());
+        assert_eq!(None, number.as_u64());
+        assert_eq!("-9223372036854775808", number.to_string());
+
+        let number = parse("18446744073709551615");
+        assert_eq!(Some(u64::MAX), number.as_u64());
+        assert_eq!(None, number.as_i64());
+        assert_eq!("18446744073709551615", number.to_string());
+
+        let number = parse("123456789012345678901234567890");
+        assert_eq!(None, number.as_u64());
+        assert_eq!("123456789012345678901234567890", number.to_string());
+        assert_eq!("-123456789012345678901234567890e2", parse("-123456789012345678901234567890e2").to_string());
+
+        let number = parse("1.5");
+        assert_eq!(None, number.as_i64());
+        assert_eq!(Some(1.5), number.as_f64());
+
+        assert_eq!(Some(1500), parse("1.5e3").as_i64());
+        assert_eq!(Some(1500), parse("15e2").as_u64());
+        assert_eq!(None, parse("15e-1").as_i64());
+        assert_eq!(Some(1.5), parse("15e-1").as_f64());
+        assert_eq!(None, parse("1e400").as_f64());
+        assert_eq!(None, parse("1e400").as_i64());
+        assert_eq!(None, parse("1e99999999999").as_f64());
+        assert_eq!(None, parse("1e99999999999").as_u64());
+        assert_eq!("1e99999999999", parse("1e99999999999").to_string());
+        assert_eq!("1e-99999999999", parse("1e-99999999999").to_string());
+        assert_eq!("0.1", parse("0.1").to_string());
+        assert_eq!(Some(0.1), parse("0.1").as_f64());
+
+        let number = parse("-0");
+        assert_eq!("-0", number.to_string());
+        assert!(number.as_f64().unwrap().is_sign_negative());
+        assert_eq!(Some(0), number.as_i64());
+
+        assert_eq!("16777217", Number::from(16777217).to_string());
+        assert_eq!("-3", Number::from(-3_i64).to_string());
+        assert_eq!("2.0", Number::from(2.0).to_string());
+        assert_eq!("12.1", Number::from(12.1_f32).to_string());
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn number_verbatim() {
+        let input = "[1.00000000000000000000001E+2, 123456789012345678901234567890]";
+        let list = crate::parser_value(input.chars()).unwrap();
+        assert_eq!(input.replace(' ', ""), list.to_string());
+        let big = list.as_list().unwrap().get(1).unwrap().as_number().unwrap();
+        assert_eq!(None, big.as_u64());
+        assert_eq!(None, list.as_list().unwrap().get(0).unwrap().as_number().unwrap().as_i64());
+        assert_eq!(Some(1500), crate::parser_value("1.500e3".chars()).unwrap().as_number().unwrap().as_i64());
+        assert_eq!(Some(-5), crate::parser_value("-0.5E1".chars()).unwrap().as_number().unwrap().as_i64());
     }
 
     #[test]
@@ -0,0 +1,410 @@
+use std::fmt;
+
+use crate::error::Error;
+use crate::objects::{array, object, TypeJson};
+use crate::pointer::{array_index, JsonPointer};
+
+#[derive(Clone, PartialEq)]
+pub enum PatchOperation {
+    Add {
+        path: JsonPointer,
+        value: TypeJson,
+    },
+    Remove {
+        path: JsonPointer,
+    },
+    Replace {
+        path: JsonPointer,
+        value: TypeJson,
+    },
+    Move {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Copy {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Test {
+        path: JsonPointer,
+        value: TypeJson,
+    },
+}
+
+impl PatchOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+            PatchOperation::Move { .. } => "move",
+            PatchOperation::Copy { .. } => "copy",
+            PatchOperation::Test { .. } => "test",
+        }
+    }
+
+    pub fn path(&self) -> &JsonPointer {
+        match self {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Replace { path, .. }
+            | PatchOperation::Move { path, .. }
+            | PatchOperation::Copy { path, .. }
+            | PatchOperation::Test { path, .. } => path,
+        }
+    }
+
+    pub fn from_json(json: &TypeJson) -> Result<PatchOperation, PatchErrorKind> {
+        let obj = json.as_object().ok_or(PatchErrorKind::NotAnObject)?;
+        let pointer = |name: &'static str| match obj.get(name) {
+            Some(TypeJson::Text(text)) => JsonPointer::parse(text).map_err(|_| PatchErrorKind::InvalidMember(name)),
+            Some(_) => Err(PatchErrorKind::InvalidMember(name)),
+            None => Err(PatchErrorKind::MissingMember(name)),
+        };
+        let value = || obj.get("value").cloned().ok_or(PatchErrorKind::MissingMember("value"));
+        let op = match obj.get("op") {
+            Some(TypeJson::Text(op)) => op.as_str(),
+            Some(_) => return Err(PatchErrorKind::InvalidMember("op")),
+            None => return Err(PatchErrorKind::MissingMember("op")),
+        };
+        Ok(match op {
+            "add" => PatchOperation::Add { path: pointer("path")?, value: value()? },
+            "remove" => PatchOperation::Remove { path: pointer("path")? },
+            "replace" => PatchOperation::Replace { path: pointer("path")?, value: value()? },
+            "move" => PatchOperation::Move { from: pointer("from")?, path: pointer("path")? },
+            "copy" => PatchOperation::Copy { from: pointer("from")?, path: pointer("path")? },
+            "test" => PatchOperation::Test { path: pointer("path")?, value: value()? },
+            op => return Err(PatchErrorKind::UnknownOperation(String::from(op))),
+        })
+    }
+
+    pub fn to_json(&self) -> TypeJson {
+        let mut obj = object();
+        obj.set("op", self.name());
+        obj.set("path", self.path().to_string());
+        match self {
+            PatchOperation::Move { from, .. } | PatchOperation::Copy { from, .. } => obj.set("from", from.to_string()),
+            PatchOperation::Add { value, .. }
+            | PatchOperation::Replace { value, .. }
+            | PatchOperation::Test { value, .. } => obj.set("value", value.clone()),
+            PatchOperation::Remove { .. } => {},
+        }
+        obj.into()
+    }
+
+    fn apply(&self, root: &mut TypeJson) -> Result<(), PatchErrorKind> {
+        match self {
+            PatchOperation::Add { path, value } => add(root, path, value.clone()),
+            PatchOperation::Remove { path } => remove(root, path).map(|_| ()),
+            PatchOperation::Replace { path, value } => match path.resolve_mut(root) {
+                Some(node) => {
+                    *node = value.clone();
+                    Ok(())
+                },
+                None => Err(PatchErrorKind::NotFound(path.clone())),
+            },
+            PatchOperation::Move { from, path } => {
+                if from == path {
+                    return from.resolve(root).map(|_| ()).ok_or_else(|| PatchErrorKind::NotFound(from.clone()));
+                }
+                if path.starts_with(from) {
+                    return Err(PatchErrorKind::MoveIntoChild(path.clone()));
+                }
+                let value = remove(root, from)?;
+                add(root, path, value)
+            },
+            PatchOperation::Copy { from, path } => match from.resolve(root) {
+                Some(value) => add(root, path, value.clone()),
+                None => Err(PatchErrorKind::NotFound(from.clone())),
+            },
+            PatchOperation::Test { path, value } => match path.resolve(root) {
+                Some(node) if node == value => Ok(()),
+                Some(_) => Err(PatchErrorKind::TestFailed(path.clone())),
+                None => Err(PatchErrorKind::NotFound(path.clone())),
+            },
+        }
+    }
+}
+
+fn add(root: &mut TypeJson, path: &JsonPointer, value: TypeJson) -> Result<(), PatchErrorKind> {
+    let (parent, last) = match path.parent() {
+        Some(split) => split,
+        None => {
+            *root = value;
+            return Ok(());
+        },
+    };
+    match parent.resolve_mut(root) {
+        Some(TypeJson::Object(obj)) => obj.set(last, value),
+        Some(TypeJson::List(list)) if last == "-" => list.add(value),
+        Some(TypeJson::List(list)) => match array_index(last) {
+            Some(index) if index <= list.len() => list.insert(index, value),
+            _ => return Err(PatchErrorKind::NotFound(path.clone())),
+        },
+        _ => return Err(PatchErrorKind::NotFound(path.clone())),
+    }
+    Ok(())
+}
+
+fn remove(root: &mut TypeJson, path: &JsonPointer) -> Result<TypeJson, PatchErrorKind> {
+    let not_found = || PatchErrorKind::NotFound(path.clone());
+    let (parent, last) = path.parent().ok_or_else(not_found)?;
+    match parent.resolve_mut(root) {
+        Some(TypeJson::Object(obj)) => obj.remove(last).ok_or_else(not_found),
+        Some(TypeJson::List(list)) => match array_index(last) {
+            Some(index) if index < list.len() => Ok(list.remove(index)),
+            _ => Err(not_found()),
+        },
+        _ => Err(not_found()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchErrorKind {
+    NotAnObject,
+    MissingMember(&'static str),
+    InvalidMember(&'static str),
+    UnknownOperation(String),
+    NotFound(JsonPointer),
+    TestFailed(JsonPointer),
+    MoveIntoChild(JsonPointer),
+}
+
+impl fmt::Display for PatchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchErrorKind::NotAnObject => write!(f, "operation is not an object"),
+            PatchErrorKind::MissingMember(name) => write!(f, "missing member {:?}", name),
+            PatchErrorKind::InvalidMember(name) => write!(f, "invalid member {:?}", name),
+            PatchErrorKind::UnknownOperation(op) => write!(f, "unknown operation {:?}", op),
+            PatchErrorKind::NotFound(path) => write!(f, "path \"{}\" does not exist", path),
+            PatchErrorKind::TestFailed(path) => write!(f, "test failed at \"{}\"", path),
+            PatchErrorKind::MoveIntoChild(path) => write!(f, "cannot move a value into its own child \"{}\"", path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    operation: usize,
+    kind: PatchErrorKind,
+}
+
+impl PatchError {
+    pub fn operation(&self) -> usize {
+        self.operation
+    }
+
+    pub fn kind(&self) -> &PatchErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "patch operation {}: {}", self.operation, self.kind)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct JsonPatch {
+    operations: Vec<PatchOperation>,
+}
+
+impl JsonPatch {
+    pub fn new() -> JsonPatch {
+        JsonPatch {
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn from_json(json: &TypeJson) -> Result<JsonPatch, Error> {
+        let list = json.as_list().ok_or(Error::Conversion {
+            expected: "array",
+            found: json.type_name(),
+        })?;
+        let operations = list.iter()
+            .enumerate()
+            .map(|(operation, json)| PatchOperation::from_json(json).map_err(|kind| PatchError { operation, kind }))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(JsonPatch {
+            operations,
+        })
+    }
+
+    pub fn to_json(&self) -> TypeJson {
+        let mut list = array();
+        for operation in &self.operations {
+            list.add(operation.to_json());
+        }
+        list.into()
+    }
+
+    pub fn diff(from: &TypeJson, to: &TypeJson) -> JsonPatch {
+        let mut patch = JsonPatch::new();
+        diff_values(&mut patch, &JsonPointer::new(), from, to);
+        patch
+    }
+
+    pub fn push(&mut self, operation: PatchOperation) {
+        self.operations.push(operation);
+    }
+
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.operations
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub fn apply(&self, target: &mut TypeJson) -> Result<(), Error> {
+        let mut patched = target.clone();
+        for (operation, op) in self.operations.iter().enumerate() {
+            op.apply(&mut patched).map_err(|kind| PatchError { operation, kind })?;
+        }
+        *target = patched;
+        Ok(())
+    }
+}
+
+fn child(path: &JsonPointer, token: impl Into<String>) -> JsonPointer {
+    let mut child = path.clone();
+    child.push(token);
+    child
+}
+
+fn diff_values(patch: &mut JsonPatch, path: &JsonPointer, from: &TypeJson, to: &TypeJson) {
+    if from == to {
+        return;
+    }
+    match (from, to) {
+        (TypeJson::Object(from), TypeJson::Object(to)) => {
+            for (key, value) in from.iter() {
+                match to.get(key) {
+                    Some(other) => diff_values(patch, &child(path, key.as_str()), value, other),
+                    None => patch.push(PatchOperation::Remove { path: child(path, key.as_str()) }),
+                }
+            }
+            for (key, value) in to.iter() {
+                if from.get(key).is_none() {
+                    patch.push(PatchOperation::Add { path: child(path, key.as_str()), value: value.clone() });
+                }
+            }
+        },
+        (TypeJson::List(from), TypeJson::List(to)) => {
+            for (i, (value, other)) in from.iter().zip(to.iter()).enumerate() {
+                diff_values(patch, &child(path, i.to_string()), value, other);
+            }
+            for i in (to.len()..from.len()).rev() {
+                patch.push(PatchOperation::Remove { path: child(path, i.to_string()) });
+            }
+            for (i, value) in to.iter().enumerate().skip(from.len()) {
+                patch.push(PatchOperation::Add { path: child(path, i.to_string()), value: value.clone() });
+            }
+        },
+        _ => patch.push(PatchOperation::Replace { path: path.clone(), value: to.clone() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::objects::*;
+    use crate::patch::*;
+
+    fn json(text: &str) -> TypeJson {
+        crate::from_str(text).unwrap()
+    }
+
+    fn patched(target: &str, patch: &str) -> Result<String, Error> {
+        let mut target = json(target);
+        JsonPatch::from_json(&json(patch))?.apply(&mut target)?;
+        Ok(target.to_string())
+    }
+
+    #[test]
+    fn patch_apply() {
+        assert_eq!("{\"baz\":\"qux\",\"foo\":\"bar\",\"hello\":[\"world\"]}",
+            patched(r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "add", "path": "/hello", "value": ["world"]}]"#).unwrap());
+        assert_eq!("{\"foo\":[\"bar\",\"qux\",\"baz\"]}",
+            patched(r#"{"foo": ["bar", "baz"]}"#, r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#).unwrap());
+        assert_eq!("[1,2,3]", patched("[1, 2]", r#"[{"op": "add", "path": "/-", "value": 3}]"#).unwrap());
+        assert_eq!("{\"foo\":[\"bar\",\"baz\"]}",
+            patched(r#"{"foo": ["bar", "qux", "baz"]}"#, r#"[{"op": "remove", "path": "/foo/1"}]"#).unwrap());
+        assert_eq!("{\"baz\":\"boo\"}", patched(r#"{"baz": "qux"}"#, r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#).unwrap());
+        assert_eq!("{\"foo\":{\"bar\":\"baz\"},\"qux\":{\"corge\":\"grault\",\"thud\":\"fred\"}}",
+            patched(r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#).unwrap());
+        assert_eq!("{\"foo\":[\"all\",\"cows\",\"eat\",\"grass\"]}",
+            patched(r#"{"foo": ["all", "grass", "cows", "eat"]}"#, r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#).unwrap());
+        assert_eq!("{\"a\":[1],\"b\":[1]}", patched(r#"{"a": [1]}"#, r#"[{"op": "copy", "from": "/a", "path": "/b"}]"#).unwrap());
+        assert_eq!("{\"n\":1.0}", patched(r#"{"n": 1.0}"#, r#"[{"op": "test", "path": "/n", "value": 1}]"#).unwrap());
+        assert_eq!("7", patched(r#"{"a": 1}"#, r#"[{"op": "replace", "path": "", "value": 7}]"#).unwrap());
+
+        let mut target = json(r#"{"a": 1, "b": [true]}"#);
+        let patch = JsonPatch::from_json(&json(r#"[
+            {"op": "add", "path": "/c", "value": 3},
+            {"op": "remove", "path": "/b/0"},
+            {"op": "test", "path": "/a", "value": 2}
+        ]"#)).unwrap();
+        match patch.apply(&mut target) {
+            Err(Error::Patch(error)) => {
+                assert_eq!(2, error.operation());
+                assert_eq!(&PatchErrorKind::TestFailed(crate::JsonPointer::parse("/a").unwrap()), error.kind());
+                assert_eq!("patch operation 2: test failed at \"/a\"", error.to_string());
+            },
+            _ => assert!(false),
+        }
+        assert_eq!("{\"a\":1,\"b\":[true]}", target.to_string());
+    }
+
+    #[test]
+    fn patch_errors() {
+        let kind = |target: &str, patch: &str| match patched(target, patch) {
+            Err(Error::Patch(error)) => error.kind().clone(),
+            _ => panic!("expected a patch error"),
+        };
+        let pointer = |text: &str| crate::JsonPointer::parse(text).unwrap();
+        assert_eq!(PatchErrorKind::NotFound(pointer("/a/b")), kind("{}", r#"[{"op": "add", "path": "/a/b", "value": 1}]"#));
+        assert_eq!(PatchErrorKind::NotFound(pointer("/3")), kind("[1]", r#"[{"op": "add", "path": "/3", "value": 1}]"#));
+        assert_eq!(PatchErrorKind::NotFound(pointer("/01")), kind("[1, 2]", r#"[{"op": "remove", "path": "/01"}]"#));
+        assert_eq!(PatchErrorKind::NotFound(pointer("/x")), kind("{}", r#"[{"op": "replace", "path": "/x", "value": 1}]"#));
+        assert_eq!(PatchErrorKind::MoveIntoChild(pointer("/a/b")), kind(r#"{"a": {}}"#, r#"[{"op": "move", "from": "/a", "path": "/a/b"}]"#));
+        assert_eq!(PatchErrorKind::MissingMember("value"), kind("{}", r#"[{"op": "add", "path": "/a"}]"#));
+        assert_eq!(PatchErrorKind::InvalidMember("path"), kind("{}", r#"[{"op": "remove", "path": "a"}]"#));
+        assert_eq!(PatchErrorKind::UnknownOperation(String::from("merge")), kind("{}", r#"[{"op": "merge", "path": "/a"}]"#));
+        assert_eq!(PatchErrorKind::NotAnObject, kind("{}", "[1]"));
+        assert!(matches!(JsonPatch::from_json(&json("{}")), Err(Error::Conversion { expected: "array", found: "object" })));
+    }
+
+    #[test]
+    fn patch_diff() {
+        let pairs = [
+            (r#"{"a": 1, "b": {"c": [1, 2, 3], "d": "x"}, "e": null}"#, r#"{"a": 1, "b": {"c": [1, 5], "f": true}, "g": []}"#),
+            ("[1, 2]", "[1, 2, {\"a\": 1}, 4]"),
+            ("[]", "{}"),
+            ("1", "1.0"),
+        ];
+        for (from, to) in pairs {
+            let (from, to) = (json(from), json(to));
+            let patch = JsonPatch::diff(&from, &to);
+            let mut target = from.clone();
+            JsonPatch::from_json(&patch.to_json()).unwrap().apply(&mut target).unwrap();
+            assert!(target == to);
+        }
+
+        let patch = JsonPatch::diff(&json(pairs[0].0), &json(pairs[0].1));
+        assert_eq!(concat!(
+            r#"[{"op":"replace","path":"/b/c/1","value":5},{"op":"remove","path":"/b/c/2"},{"op":"remove","path":"/b/d"},"#,
+            r#"{"op":"add","path":"/b/f","value":true},{"op":"remove","path":"/e"},{"op":"add","path":"/g","value":[]}]"#,
+        ), patch.to_json().to_string());
+        assert!(JsonPatch::diff(&json("1"), &json("1.0")).is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+
+use crate::automa::{KeyParseQueryAutoma, KeyParseQueryToken, PathError};
+use crate::error::Error;
+use crate::objects::TypeJson;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+impl JsonPointer {
+    pub fn new() -> JsonPointer {
+        JsonPointer {
+            tokens: Vec::new(),
+        }
+    }
+
+    pub fn parse(pointer: &str) -> Result<JsonPointer, Error> {
+        let mut tokens = Vec::new();
+        let mut chars = pointer.char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '/' => tokens.push(String::new()),
+                _ if tokens.is_empty() => return Err(PathError::new(offset, Some(c), &["'/'"]).with_path(pointer).into()),
+                '~' => match chars.next() {
+                    Some((_, '0')) => tokens.last_mut().unwrap().push('~'),
+                    Some((_, '1')) => tokens.last_mut().unwrap().push('/'),
+                    Some((offset, c)) => return Err(PathError::new(offset, Some(c), &["'0'", "'1'"]).with_path(pointer).into()),
+                    None => return Err(PathError::new(pointer.len(), None, &["'0'", "'1'"]).with_path(pointer).into()),
+                },
+                c => tokens.last_mut().unwrap().push(c),
+            }
+        }
+        Ok(JsonPointer {
+            tokens,
+        })
+    }
+
+    pub fn from_path(path: &str) -> Result<JsonPointer, Error> {
+        let mut chars = path.chars();
+        JsonPointer::from_tokens(KeyParseQueryAutoma::new(&mut chars)).map_err(|error| match error {
+            Error::Path(error) => error.with_path(path).into(),
+            error => error,
+        })
+    }
+
+    pub fn from_tokens(tokens: impl IntoIterator<Item=KeyParseQueryToken>) -> Result<JsonPointer, Error> {
+        let mut pointer = JsonPointer::new();
+        for token in tokens {
+            match token {
+                KeyParseQueryToken::Key(key) => pointer.push(key),
+                KeyParseQueryToken::Index(index) => pointer.push(index.to_string()),
+                KeyParseQueryToken::Error(error) => return Err(error.into()),
+            }
+        }
+        Ok(pointer)
+    }
+
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn parent(&self) -> Option<(JsonPointer, &str)> {
+        let (last, parent) = self.tokens.split_last()?;
+        Some((JsonPointer {
+            tokens: parent.to_vec(),
+        }, last))
+    }
+
+    pub fn starts_with(&self, prefix: &JsonPointer) -> bool {
+        self.tokens.starts_with(&prefix.tokens)
+    }
+
+    pub fn to_tokens(&self) -> Vec<KeyParseQueryToken> {
+        self.tokens.iter()
+            .map(|token| match array_index(token) {
+                Some(index) => KeyParseQueryToken::Index(index),
+                None => KeyParseQueryToken::Key(token.clone()),
+            })
+            .collect()
+    }
+
+    pub fn tokens_in(&self, root: &TypeJson) -> Vec<KeyParseQueryToken> {
+        let mut node = Some(root);
+        self.tokens.iter()
+            .map(|token| match (node, array_index(token)) {
+                (Some(TypeJson::List(list)), Some(index)) => {
+                    node = list.get(index);
+                    KeyParseQueryToken::Index(index)
+                },
+                (Some(TypeJson::Object(object)), _) => {
+                    node = object.get(token);
+                    KeyParseQueryToken::Key(token.clone())
+                },
+                (None, Some(index)) => KeyParseQueryToken::Index(index),
+                _ => {
+                    node = None;
+                    KeyParseQueryToken::Key(token.clone())
+                },
+            })
+            .collect()
+    }
+
+    pub fn to_path(&self) -> String {
+        self.to_tokens().iter().map(|token| token.to_string()).collect()
+    }
+
+    pub fn path_in(&self, root: &TypeJson) -> String {
+        self.tokens_in(root).iter().map(|token| token.to_string()).collect()
+    }
+
+    pub fn resolve<'a>(&self, root: &'a TypeJson) -> Option<&'a TypeJson> {
+        let mut node = root;
+        for token in &self.tokens {
+            node = match node {
+                TypeJson::Object(object) => object.get(token)?,
+                TypeJson::List(list) => list.get(array_index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    pub fn resolve_mut<'a>(&self, root: &'a mut TypeJson) -> Option<&'a mut TypeJson> {
+        let mut node = root;
+        for token in &self.tokens {
+            node = match node {
+                TypeJson::Object(object) => object.get_mut(token)?,
+                TypeJson::List(list) => list.get_mut(array_index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            f.write_str("/")?;
+            for c in token.chars() {
+                match c {
+                    '~' => f.write_str("~0")?,
+                    '/' => f.write_str("~1")?,
+                    c => write!(f, "{}", c)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    match token.as_bytes() {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => token.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::automa::*;
+    use crate::error::Error;
+    use crate::objects::*;
+    use crate::pointer::*;
+
+    fn sample() -> TypeJson {
+        crate::parser_value(r#"{"k2": {"k3": {"n6": ["a", "b"]}}, "a/b": {"m~n": 1, "": 2}, "7": {"01": 3}, " sp": [true]}"#.chars()).unwrap()
+    }
+
+    #[test]
+    fn pointer_resolve() {
+        let mut root = sample();
+        assert_eq!(Some("b"), JsonPointer::parse("/k2/k3/n6/1").unwrap().resolve(&root).and_then(|node| node.as_text()));
+        assert_eq!("1", JsonPointer::parse("/a~1b/m~0n").unwrap().resolve(&root).unwrap().to_string());
+        assert_eq!("2", JsonPointer::parse("/a~1b/").unwrap().resolve(&root).unwrap().to_string());
+        assert_eq!("3", JsonPointer::parse("/7/01").unwrap().resolve(&root).unwrap().to_string());
+        assert!(JsonPointer::parse("").unwrap().resolve(&root).unwrap().as_object().is_some());
+        assert!(JsonPointer::parse("/k2/k3/n6/01").unwrap().resolve(&root).is_none());
+        assert!(JsonPointer::parse("/k2/k3/n6/-").unwrap().resolve(&root).is_none());
+        assert!(JsonPointer::parse("/k2/k3/n6/0/x").unwrap().resolve(&root).is_none());
+
+        *JsonPointer::parse("/k2/k3/n6/0").unwrap().resolve_mut(&mut root).unwrap() = "z".into();
+        assert_eq!(Some("z"), JsonPointer::parse("/k2/k3/n6/0").unwrap().resolve(&root).and_then(|node| node.as_text()));
+
+        match JsonPointer::parse("/a~2") {
+            Err(Error::Path(error)) => {
+                assert_eq!(3, error.offset());
+                assert_eq!(Some('2'), error.found());
+            },
+            _ => assert!(false),
+        }
+        assert!(JsonPointer::parse("k2").is_err());
+        assert!(JsonPointer::parse("/a~").is_err());
+    }
+
+    #[test]
+    fn pointer_round_trip() {
+        let root = sample();
+        for pointer in ["/k2/k3/n6/1", "/a~1b/m~0n", "/a~1b/", "/7/01", "/ sp/0", ""] {
+            let parsed = JsonPointer::parse(pointer).unwrap();
+            assert_eq!(pointer, parsed.to_string());
+            let path = parsed.path_in(&root);
+            let back = JsonPointer::from_path(&path).unwrap();
+            assert_eq!(parsed, back);
+            assert_eq!(pointer, back.to_string());
+            assert_eq!(parsed.resolve(&root).map(|node| node.to_string()), ReaderJson::new(&root).path_check(&path).unwrap().json().to_string().into());
+        }
+
+        assert_eq!(".k2.k3.n6[1]", JsonPointer::parse("/k2/k3/n6/1").unwrap().path_in(&root));
+        assert_eq!(".\"a/b\".\"m~n\"", JsonPointer::parse("/a~1b/m~0n").unwrap().path_in(&root));
+        assert_eq!(".7.01", JsonPointer::parse("/7/01").unwrap().path_in(&root));
+        assert_eq!("[7].01", JsonPointer::parse("/7/01").unwrap().to_path());
+        assert_eq!(vec![KeyParseQueryToken::Key(String::from("7")), KeyParseQueryToken::Key(String::from("01"))],
+            JsonPointer::parse("/7/01").unwrap().tokens_in(&root));
+        assert_eq!("/k2/k3/n6/1", JsonPointer::from_path(".k2.k3.n6[1]").unwrap().to_string());
+        assert!(JsonPointer::from_path(".k2[").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::objects::{ListJson, ObjectJson, TypeJson};
+use crate::serializer::Serializer;
+
+pub enum Indent {
+    Spaces(usize),
+    Tabs,
+}
+
+pub enum NewLine {
+    Lf,
+    CrLf,
+}
+
+pub struct PrettyPrinter {
+    indent: Indent,
+    new_line: NewLine,
+    trailing_new_line: bool,
+    compact_arrays: bool,
+}
+
+impl PrettyPrinter {
+    pub fn new() -> PrettyPrinter {
+        PrettyPrinter {
+            indent: Indent::Spaces(2),
+            new_line: NewLine::Lf,
+            trailing_new_line: false,
+            compact_arrays: false,
+        }
+    }
+
+    pub fn indent(mut self, indent: Indent) -> PrettyPrinter {
+        self.indent = indent;
+        self
+    }
+
+    pub fn new_line(mut self, new_line: NewLine) -> PrettyPrinter {
+        self.new_line = new_line;
+        self
+    }
+
+    pub fn trailing_new_line(mut self, trailing_new_line: bool) -> PrettyPrinter {
+        self.trailing_new_line = trailing_new_line;
+        self
+    }
+
+    pub fn compact_arrays(mut self, compact_arrays: bool) -> PrettyPrinter {
+        self.compact_arrays = compact_arrays;
+        self
+    }
+
+    pub fn print(&self, json: &TypeJson) -> String {
+        let mut out = String::new();
+        Serializer::pretty(&mut out, self).serialize(json).unwrap();
+        out
+    }
+
+    pub fn print_object(&self, object: &ObjectJson) -> String {
+        let mut out = String::new();
+        Serializer::pretty(&mut out, self).serialize_object(object).unwrap();
+        out
+    }
+
+    pub fn print_list(&self, list: &ListJson) -> String {
+        let mut out = String::new();
+        Serializer::pretty(&mut out, self).serialize_list(list).unwrap();
+        out
+    }
+
+    pub(crate) fn has_trailing_new_line(&self) -> bool {
+        self.trailing_new_line
+    }
+
+    pub(crate) fn is_inline(&self, list: &ListJson) -> bool {
+        self.compact_arrays && list.iter().all(|value| !matches!(value, TypeJson::Object(_) | TypeJson::List(_)))
+    }
+
+    pub(crate) fn write_new_line(&self, writer: &mut impl fmt::Write, level: usize) -> fmt::Result {
+        match self.new_line {
+            NewLine::Lf => writer.write_char('\n')?,
+            NewLine::CrLf => writer.write_str("\r\n")?,
+        }
+        for _ in 0..level {
+            match self.indent {
+                Indent::Spaces(size) => write!(writer, "{:size$}", "")?,
+                Indent::Tabs => writer.write_char('\t')?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        PrettyPrinter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::*;
+    use crate::pretty::*;
+
+    fn sample() -> ObjectJson {
+        let mut root = object();
+        root.set("name", "Foo");
+        root.set("age", 32);
+        let tags = root.list("tags");
+        tags.add("t1");
+        tags.add("t2");
+        let sub = root.object("sub");
+        sub.set("empty", object());
+        sub.set("none", array());
+        let list = sub.list("mixed");
+        list.add(1);
+        list.object().set("k", null());
+        root
+    }
+
+    #[test]
+    fn pretty_default() {
+        assert_eq!(r#"{
+  "name": "Foo",
+  "age": 32,
+  "tags": [
+    "t1",
+    "t2"
+  ],
+  "sub": {
+    "empty": {},
+    "none": [],
+    "mixed": [
+      1,
+      {
+        "k": null
+      }
+    ]
+  }
+}"#, PrettyPrinter::new().print_object(&sample()));
+    }
+
+    #[test]
+    fn pretty_options() {
+        let printer = PrettyPrinter::new()
+            .indent(Indent::Tabs)
+            .new_line(NewLine::CrLf)
+            .trailing_new_line(true)
+            .compact_arrays(true);
+        assert_eq!(
+            "{\r\n\t\"name\": \"Foo\",\r\n\t\"age\": 32,\r\n\t\"tags\": [\"t1\", \"t2\"],\r\n\t\"sub\": {\r\n\t\t\"empty\": {},\r\n\t\t\"none\": [],\r\n\t\t\"mixed\": [\r\n\t\t\t1,\r\n\t\t\t{\r\n\t\t\t\t\"k\": null\r\n\t\t\t}\r\n\t\t]\r\n\t}\r\n}\r\n",
+            printer.print(&sample().into()));
+
+        assert_eq!("\"a\\\"b\"", PrettyPrinter::new().indent(Indent::Spaces(4)).print(&"a\"b".into()));
+        let mut list = array();
+        list.add(true);
+        assert_eq!("[\n    true\n]\n", PrettyPrinter::new().indent(Indent::Spaces(4)).trailing_new_line(true).print_list(&list));
+    }
+}
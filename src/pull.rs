@@ -0,0 +1,549 @@
+use std::io;
+
+use crate::automa::{is_space, Automa, NumberAutoma, ParserError, ParserErrorKind, Position, PositionIterator, StrAutoma, StringAutoma};
+use crate::decoder::Utf8Decoder;
+use crate::error::Error;
+use crate::objects::TypeJson;
+
+pub enum JsonEvent {
+    StartObject,
+    Key(String),
+    Value(TypeJson),
+    EndObject,
+    StartArray,
+    EndArray,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Container {
+    Object,
+    Array,
+}
+
+// Container structure is tracked here rather than in the recursive container automata, which
+// could not stop between events; scalars and keys still go through the leaf automata in automa.rs.
+enum PullState {
+    Root,
+    ObjectFirst,
+    ObjectKey,
+    Colon,
+    Value,
+    ArrayFirst,
+    Next,
+    End,
+    Done,
+}
+
+pub struct PullParser<I> {
+    iter: PositionIterator<I>,
+    peeked: Option<char>,
+    state: PullState,
+    stack: Vec<Container>,
+    root: Option<Container>,
+    multiple: bool,
+    opened: bool,
+    source_error: fn(&mut I) -> Option<Error>,
+}
+
+impl <I: Iterator<Item=char>> PullParser<I> {
+    pub fn new(iter: I) -> PullParser<I> {
+        PullParser {
+            iter: PositionIterator::new(iter),
+            peeked: None,
+            state: PullState::Root,
+            stack: Vec::new(),
+            root: None,
+            multiple: false,
+            opened: false,
+            source_error: |_| None,
+        }
+    }
+
+    pub(crate) fn container(iter: I, root: Container) -> PullParser<I> {
+        let mut parser = PullParser::new(iter);
+        parser.root = Some(root);
+        parser
+    }
+
+    pub(crate) fn multiple(iter: I) -> PullParser<I> {
+        let mut parser = PullParser::new(iter);
+        parser.multiple = true;
+        parser
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn position(&self) -> Position {
+        self.iter.position
+    }
+
+    /// Skips the value the last event belongs to: the rest of a container after
+    /// `StartObject`/`StartArray`, or the whole value after a `Key`. After any
+    /// other event there is nothing to skip and this does nothing. Skipped input
+    /// is checked against the JSON grammar but no values are built.
+    pub fn skip_subtree(&mut self) -> Result<(), Error> {
+        let result = if self.opened {
+            let container = self.stack.pop().unwrap();
+            self.skip_nested(container)
+        } else if let PullState::Colon = self.state {
+            self.skip_member()
+        } else {
+            return Ok(());
+        };
+        self.opened = false;
+        match result {
+            Ok(()) => {
+                self.after_value();
+                Ok(())
+            },
+            Err(error) => Err(self.fail(error)),
+        }
+    }
+
+    fn skip_member(&mut self) -> Result<(), Error> {
+        match self.next_token()? {
+            Some(':') => {},
+            other => return Err(ParserError::unexpected(other, &["':'"]).into()),
+        }
+        match self.next_token()? {
+            Some('{') => self.skip_nested(Container::Object),
+            Some('[') => self.skip_nested(Container::Array),
+            other => self.skip_scalar(other),
+        }
+    }
+
+    fn skip_nested(&mut self, container: Container) -> Result<(), Error> {
+        let mut stack = vec![container];
+        let mut first = true;
+        while let Some(container) = stack.last().copied() {
+            let (closer, expected) = match container {
+                Container::Object => ('}', "'}'"),
+                Container::Array => (']', "']'"),
+            };
+            let mut c = self.next_token()?;
+            if c == Some(closer) {
+                stack.pop();
+                first = false;
+                continue;
+            }
+            if !first {
+                match c {
+                    Some(',') => c = self.next_token()?,
+                    other => return Err(ParserError::unexpected(other, &["','", expected]).into()),
+                }
+            }
+            first = false;
+            if let Container::Object = container {
+                match c {
+                    Some('"') => self.skip_string()?,
+                    other => return Err(ParserError::unexpected(other, &["string"]).into()),
+                }
+                match self.next_token()? {
+                    Some(':') => c = self.next_token()?,
+                    other => return Err(ParserError::unexpected(other, &["':'"]).into()),
+                }
+            }
+            match c {
+                Some('{') => stack.push(Container::Object),
+                Some('[') => stack.push(Container::Array),
+                other => {
+                    self.skip_scalar(other)?;
+                    continue;
+                },
+            }
+            first = true;
+        }
+        Ok(())
+    }
+
+    fn skip_scalar(&mut self, c: Option<char>) -> Result<(), Error> {
+        let (rest, literal) = match c {
+            Some('"') => return self.skip_string(),
+            Some(c) if c == '-' || c.is_ascii_digit() => return self.skip_number(c),
+            Some('t') => ("rue", "'true'"),
+            Some('f') => ("alse", "'false'"),
+            Some('n') => ("ull", "'null'"),
+            other => return Err(ParserError::unexpected(other, &["value"]).into()),
+        };
+        for expected in rest.chars() {
+            match self.next_char()? {
+                Some(c) if c == expected => {},
+                other => return Err(ParserError::unexpected(other, &[literal]).into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_string(&mut self) -> Result<(), Error> {
+        loop {
+            match self.next_char()? {
+                Some('"') => return Ok(()),
+                Some('\\') => match self.next_char()? {
+                    Some('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't') => {},
+                    Some('u') => for _ in 0..4 {
+                        match self.next_char()? {
+                            Some(c) if c.is_ascii_hexdigit() => {},
+                            _ => return Err(ParserError::new(ParserErrorKind::InvalidEscape, &["hex digit"]).into()),
+                        }
+                    },
+                    Some(c) => return Err(ParserError::unexpected(Some(c), &["escape character"]).into()),
+                    None => return Err(ParserError::unexpected(None, &["'\"'"]).into()),
+                },
+                Some(_) => {},
+                None => return Err(ParserError::unexpected(None, &["'\"'"]).into()),
+            }
+        }
+    }
+
+    fn skip_number(&mut self, first: char) -> Result<(), Error> {
+        let mut c = Some(first);
+        if c == Some('-') {
+            c = self.next_char()?;
+        }
+        c = match c {
+            Some('0') => self.next_char()?,
+            Some(c) if c.is_ascii_digit() => self.skip_digits()?,
+            _ => return Err(ParserError::new(ParserErrorKind::InvalidNumber, &["digit"]).into()),
+        };
+        if c == Some('.') {
+            c = self.skip_required_digits()?;
+        }
+        if let Some('e' | 'E') = c {
+            c = match self.next_char()? {
+                Some('+' | '-') => self.skip_required_digits()?,
+                other => {
+                    self.peeked = other;
+                    self.skip_required_digits()?
+                },
+            };
+        }
+        self.peeked = c;
+        Ok(())
+    }
+
+    fn skip_required_digits(&mut self) -> Result<Option<char>, Error> {
+        match self.next_char()? {
+            Some(c) if c.is_ascii_digit() => self.skip_digits(),
+            _ => Err(ParserError::new(ParserErrorKind::InvalidNumber, &["digit"]).into()),
+        }
+    }
+
+    fn skip_digits(&mut self) -> Result<Option<char>, Error> {
+        loop {
+            match self.next_char()? {
+                Some(c) if c.is_ascii_digit() => {},
+                other => return Ok(other),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<char>, Error> {
+        loop {
+            match self.next_char()? {
+                Some(c) if is_space(c) => continue,
+                c => return Ok(c),
+            }
+        }
+    }
+
+    fn fail(&mut self, error: Error) -> Error {
+        self.state = PullState::Done;
+        let error = (self.source_error)(&mut self.iter.iterator).unwrap_or(error);
+        self.iter.locate(error)
+    }
+
+    fn next_char(&mut self) -> Result<Option<char>, Error> {
+        match self.peeked.take().or_else(|| self.iter.next()) {
+            Some(c) => Ok(Some(c)),
+            None => match (self.source_error)(&mut self.iter.iterator) {
+                Some(error) => Err(error),
+                None => Ok(None),
+            },
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<JsonEvent>, Error> {
+        loop {
+            if let PullState::Done = self.state {
+                return Ok(None);
+            }
+            let c = match self.next_char()? {
+                Some(c) if is_space(c) => continue,
+                c => c,
+            };
+            match self.state {
+                PullState::Done => return Ok(None),
+                PullState::End => match c {
+                    None => {
+                        self.state = PullState::Done;
+                        return Ok(None);
+                    },
+                    Some(c) if self.multiple => return self.value(Some(c), &["value"]).map(Some),
+                    Some(_) => return Err(ParserError::new(ParserErrorKind::TrailingData, &["end of input"]).into()),
+                },
+                PullState::Root => match (self.root, c) {
+                    (Some(Container::Object), Some('{')) => return Ok(Some(self.open(Container::Object))),
+                    (Some(Container::Object), other) => return Err(ParserError::unexpected(other, &["'{'"]).into()),
+                    (Some(Container::Array), Some('[')) => return Ok(Some(self.open(Container::Array))),
+                    (Some(Container::Array), other) => return Err(ParserError::unexpected(other, &["'['"]).into()),
+                    (None, None) if self.multiple => {
+                        self.state = PullState::Done;
+                        return Ok(None);
+                    },
+                    (None, c) => return self.value(c, &["value"]).map(Some),
+                },
+                PullState::Value => return self.value(c, &["value"]).map(Some),
+                PullState::ArrayFirst => match c {
+                    Some(']') => return Ok(Some(self.close())),
+                    c => return self.value(c, &["value", "']'"]).map(Some),
+                },
+                PullState::ObjectFirst => match c {
+                    Some('}') => return Ok(Some(self.close())),
+                    Some('"') => return self.key().map(Some),
+                    other => return Err(ParserError::unexpected(other, &["string", "'}'"]).into()),
+                },
+                PullState::ObjectKey => match c {
+                    Some('"') => return self.key().map(Some),
+                    other => return Err(ParserError::unexpected(other, &["string"]).into()),
+                },
+                PullState::Colon => match c {
+                    Some(':') => self.state = PullState::Value,
+                    other => return Err(ParserError::unexpected(other, &["':'"]).into()),
+                },
+                PullState::Next => match (self.stack.last(), c) {
+                    (Some(Container::Object), Some(',')) => self.state = PullState::ObjectKey,
+                    (Some(Container::Array), Some(',')) => self.state = PullState::Value,
+                    (Some(Container::Object), Some('}')) | (Some(Container::Array), Some(']')) => return Ok(Some(self.close())),
+                    (Some(Container::Object), other) => return Err(ParserError::unexpected(other, &["','", "'}'"]).into()),
+                    (_, other) => return Err(ParserError::unexpected(other, &["','", "']'"]).into()),
+                },
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<JsonEvent, Error> {
+        let key = StrAutoma::new().process('"', &mut self.iter)?;
+        self.state = PullState::Colon;
+        Ok(JsonEvent::Key(key))
+    }
+
+    fn value(&mut self, c: Option<char>, expected: &[&str]) -> Result<JsonEvent, Error> {
+        let str_automa = StrAutoma::new();
+        let number_automa = NumberAutoma::new();
+        let null_automa = StringAutoma::from("null");
+        let true_automa = StringAutoma::from("true");
+        let false_automa = StringAutoma::from("false");
+        let value = match c {
+            Some('{') => return Ok(self.open(Container::Object)),
+            Some('[') => return Ok(self.open(Container::Array)),
+            Some(c) if str_automa.can_start(c) => str_automa.process(c, &mut self.iter)?.into(),
+            Some(c) if number_automa.can_start(c) => {
+                let (number, extra) = number_automa.process(c, &mut self.iter)?;
+                self.peeked = extra;
+                number.into()
+            },
+            Some(c) if null_automa.can_start(c) => {
+                null_automa.process(c, &mut self.iter)?;
+                TypeJson::Null
+            },
+            Some(c) if true_automa.can_start(c) => {
+                true_automa.process(c, &mut self.iter)?;
+                true.into()
+            },
+            Some(c) if false_automa.can_start(c) => {
+                false_automa.process(c, &mut self.iter)?;
+                false.into()
+            },
+            other => return Err(ParserError::unexpected(other, expected).into()),
+        };
+        self.after_value();
+        Ok(JsonEvent::Value(value))
+    }
+
+    fn open(&mut self, container: Container) -> JsonEvent {
+        self.stack.push(container);
+        self.opened = true;
+        match container {
+            Container::Object => {
+                self.state = PullState::ObjectFirst;
+                JsonEvent::StartObject
+            },
+            Container::Array => {
+                self.state = PullState::ArrayFirst;
+                JsonEvent::StartArray
+            },
+        }
+    }
+
+    fn close(&mut self) -> JsonEvent {
+        let container = self.stack.pop();
+        self.after_value();
+        match container {
+            Some(Container::Object) => JsonEvent::EndObject,
+            _ => JsonEvent::EndArray,
+        }
+    }
+
+    fn after_value(&mut self) {
+        self.state = if self.stack.is_empty() { PullState::End } else { PullState::Next };
+    }
+}
+
+impl <R: io::Read> PullParser<Utf8Decoder<R>> {
+    pub fn from_reader(reader: R) -> PullParser<Utf8Decoder<R>> {
+        let mut parser = PullParser::new(Utf8Decoder::new(reader));
+        parser.source_error = |decoder| decoder.take_error();
+        parser
+    }
+
+    pub(crate) fn multiple_reader(reader: R) -> PullParser<Utf8Decoder<R>> {
+        let mut parser = PullParser::from_reader(reader);
+        parser.multiple = true;
+        parser
+    }
+}
+
+impl <I: Iterator<Item=char>> Iterator for PullParser<I> {
+    type Item = Result<JsonEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.opened = false;
+        match self.step() {
+            Ok(event) => event.map(Ok),
+            Err(error) => Some(Err(self.fail(error))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::pull::*;
+
+    fn events(input: &str) -> Vec<String> {
+        PullParser::new(input.chars())
+            .map(|event| match event {
+                Ok(JsonEvent::StartObject) => String::from("{"),
+                Ok(JsonEvent::EndObject) => String::from("}"),
+                Ok(JsonEvent::StartArray) => String::from("["),
+                Ok(JsonEvent::EndArray) => String::from("]"),
+                Ok(JsonEvent::Key(key)) => format!("{}:", key),
+                Ok(JsonEvent::Value(value)) => value.to_string(),
+                Err(error) => format!("error: {}", error),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pull_events() {
+        assert_eq!(vec!["{", "a:", "1", "b:", "[", "true", "{", "}", "[", "]", "-2.5e3", "]", "c:", "null", "}"],
+            events(" {\"a\": 1, \"b\": [true, {}, [], -2.5e3], \"c\": null} "));
+        assert_eq!(vec!["\"x\""], events("\"x\""));
+        assert_eq!(vec!["12"], events("12"));
+        assert_eq!(vec!["[", "1", "error: unexpected character ',' at line 1, column 4 (byte 3), expected value"], events("[1,,]"));
+        assert_eq!(vec!["[", "1", "error: unexpected character ']' at line 1, column 4 (byte 3), expected value"], events("[1,]"));
+        assert_eq!(vec!["{", "error: unexpected end of input at line 1, column 2 (byte 1), expected one of string, '}'"], events("{"));
+        assert_eq!(vec!["1", "error: trailing data after root value at line 1, column 3 (byte 2), expected end of input"], events("1 2"));
+    }
+
+    #[test]
+    fn pull_skip() {
+        let mut parser = PullParser::new("{\"skip\": {\"a\": [1, {\"b\": 2}]}, \"keep\": [3, [4]]}".chars());
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::StartObject))));
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Key(_)))));
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::StartObject))));
+        assert_eq!(2, parser.depth());
+        parser.skip_subtree().unwrap();
+        assert_eq!(1, parser.depth());
+        match parser.next() {
+            Some(Ok(JsonEvent::Key(key))) => assert_eq!("keep", key),
+            _ => assert!(false),
+        }
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::StartArray))));
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Value(_)))));
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::StartArray))));
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::EndArray))));
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::EndObject))));
+        assert!(parser.next().is_none());
+
+        let mut parser = PullParser::new("{\"a\": [1, [2]], \"b\": \"x\", \"c\": 3}".chars());
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::StartObject))));
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Key(_)))));
+        parser.skip_subtree().unwrap();
+        assert_eq!(1, parser.depth());
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Key(_)))));
+        parser.skip_subtree().unwrap();
+        match parser.next() {
+            Some(Ok(JsonEvent::Key(key))) => assert_eq!("c", key),
+            _ => assert!(false),
+        }
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Value(_)))));
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::EndObject))));
+
+        let mut parser = PullParser::new("{\"a\": {\"s\": \"]}\\\"{[\", \"t\": [1e-2, true]}, \"b\": -2}".chars());
+        parser.next();
+        parser.next();
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Key(_)))));
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::EndObject))));
+        assert!(parser.next().is_none());
+
+        let mut parser = PullParser::new("{\"a\": [true, false, null, -0.5e+3, 0, {}, [], \"\\u00e9\"], \"b\": 1E2}".chars());
+        parser.next();
+        parser.next();
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Key(_)))));
+        parser.skip_subtree().unwrap();
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::EndObject))));
+
+        let malformed = [
+            "{\"a\": [1 }, \"b\": 2}", "{\"a\": xyz, \"b\": 2}", "{\"a\": [1, }", "{\"a\": {\"b\" 1}}", "{\"a\": {1: 2}}",
+            "{\"a\": [1 2]}", "{\"a\": [1,]}", "{\"a\": [01]}", "{\"a\": tru}", "{\"a\": [1.e5]}", "{\"a\": -}", "{\"a\": \"\\x\"}",
+        ];
+        for input in malformed {
+            let mut parser = PullParser::new(input.chars());
+            parser.next();
+            parser.next();
+            assert!(parser.skip_subtree().is_err(), "{}", input);
+            assert!(parser.next().is_none());
+        }
+
+        let mut parser = PullParser::new("{\"a\": [{\"b\": 1]}".chars());
+        parser.next();
+        parser.next();
+        match parser.skip_subtree() {
+            Err(Error::Parser(error)) => assert_eq!("unexpected character ']' at line 1, column 15 (byte 14), expected one of ',', '}'", error.to_string()),
+            _ => assert!(false),
+        }
+
+        let mut parser = PullParser::new("{\"a\": [1, \"]".chars());
+        parser.next();
+        parser.next();
+        match parser.skip_subtree() {
+            Err(Error::Parser(error)) => assert_eq!(&ParserErrorKind::Eof, error.kind()),
+            _ => assert!(false),
+        }
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn pull_reader() {
+        let mut parser = PullParser::from_reader(&b"[\"ok\", \"\xff\"]"[..]);
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::StartArray))));
+        assert!(matches!(parser.next(), Some(Ok(JsonEvent::Value(_)))));
+        match parser.next() {
+            Some(Err(Error::Parser(error))) => {
+                assert_eq!(&ParserErrorKind::InvalidUtf8, error.kind());
+                assert_eq!(8, error.position().offset);
+            },
+            _ => assert!(false),
+        }
+        assert!(parser.next().is_none());
+    }
+}
@@ -0,0 +1,776 @@
+use std::cmp::Ordering;
+
+use crate::automa::{is_char, is_number, parser_value, Automa, PathError, StrAutoma};
+use crate::error::Error;
+use crate::iregexp::Regexp;
+use crate::objects::TypeJson;
+
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Expr),
+}
+
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+struct FilterQuery {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+impl FilterQuery {
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| !segment.descendant
+            && segment.selectors.len() == 1
+            && matches!(segment.selectors[0], Selector::Name(_) | Selector::Index(_)))
+    }
+}
+
+enum Function {
+    Length(Box<Comparable>),
+    Count(FilterQuery),
+    Value(FilterQuery),
+}
+
+enum Comparable {
+    Literal(TypeJson),
+    Query(FilterQuery),
+    Function(Function),
+}
+
+#[derive(Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Exists(FilterQuery),
+    Compare(Comparable, CompareOp, Comparable),
+    Match(Comparable, Comparable, bool),
+}
+
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<JsonPath, Error> {
+        let mut parser = QueryParser {
+            query,
+            chars: query.char_indices().collect(),
+            index: 0,
+        };
+        Ok(JsonPath {
+            segments: parser.query()?,
+        })
+    }
+
+    pub fn select<'a>(&self, root: &'a TypeJson) -> Vec<&'a TypeJson> {
+        apply(&self.segments, root, root)
+    }
+}
+
+struct QueryParser<'q> {
+    query: &'q str,
+    chars: Vec<(usize, char)>,
+    index: usize,
+}
+
+impl <'q> QueryParser<'q> {
+    fn peek(&self) -> Option<char> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.chars.get(self.index + n).map(|(_, c)| *c)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.index += 1;
+        }
+        c
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.chars.get(self.index).map(|(offset, _)| *offset).unwrap_or(self.query.len())
+    }
+
+    fn error(&self, expected: &[&str]) -> Error {
+        PathError::new(self.offset(), self.peek(), expected).with_path(self.query).into()
+    }
+
+    fn expect(&mut self, c: char, expected: &str) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&[expected]))
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.index += 1;
+        }
+    }
+
+    fn query(&mut self) -> Result<Vec<Segment>, Error> {
+        self.eat('$');
+        let segments = self.segments()?;
+        self.skip_blank();
+        match self.peek() {
+            None => Ok(segments),
+            Some(_) => Err(self.error(&["'.'", "'['", "end of query"])),
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, Error> {
+        let mut segments = Vec::new();
+        loop {
+            let index = self.index;
+            self.skip_blank();
+            match self.peek() {
+                Some('.' | '[') => segments.push(self.segment()?),
+                _ => {
+                    self.index = index;
+                    return Ok(segments);
+                },
+            }
+        }
+    }
+
+    fn segment(&mut self) -> Result<Segment, Error> {
+        if self.eat('[') {
+            return Ok(Segment {
+                descendant: false,
+                selectors: self.bracket()?,
+            });
+        }
+        self.bump();
+        let descendant = self.eat('.');
+        let selector = match self.peek() {
+            Some('[') if descendant => {
+                self.bump();
+                return Ok(Segment {
+                    descendant,
+                    selectors: self.bracket()?,
+                });
+            },
+            Some('*') => {
+                self.bump();
+                Selector::Wildcard
+            },
+            Some('"') => Selector::Name(self.string()?),
+            Some(c) if is_name_char(c) => {
+                let mut name = String::new();
+                while let Some(c) = self.peek().filter(|c| is_name_char(*c)) {
+                    name.push(c);
+                    self.index += 1;
+                }
+                Selector::Name(name)
+            },
+            _ if descendant => return Err(self.error(&["key character", "'\"'", "'*'", "'['"])),
+            _ => return Err(self.error(&["key character", "'\"'", "'*'"])),
+        };
+        Ok(Segment {
+            descendant,
+            selectors: vec![selector],
+        })
+    }
+
+    fn bracket(&mut self) -> Result<Vec<Selector>, Error> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.selector()?);
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            if !self.eat(',') {
+                return Err(self.error(&["','", "']'"]));
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, Error> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.bump();
+                Ok(Selector::Wildcard)
+            },
+            Some('?') => {
+                self.bump();
+                let expr = self.logical()?;
+                self.skip_blank();
+                Ok(Selector::Filter(expr))
+            },
+            Some(c) if c == '-' || c == ':' || is_number(c) => self.index_or_slice(),
+            _ => Err(self.error(&["string", "'*'", "'?'", "index", "slice"])),
+        }
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, Error> {
+        let start = self.index;
+        self.eat('-');
+        while self.peek().filter(|c| is_number(*c)).is_some() {
+            self.index += 1;
+        }
+        match self.index - start {
+            0 => Ok(None),
+            _ => {
+                let text = self.chars[start..self.index].iter().map(|(_, c)| c).collect::<String>();
+                match text.parse() {
+                    Ok(number) => Ok(Some(number)),
+                    Err(_) => {
+                        self.index = start;
+                        Err(self.error(&["integer"]))
+                    },
+                }
+            },
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, Error> {
+        let start = self.integer()?;
+        self.skip_blank();
+        if !self.eat(':') {
+            return start.map(Selector::Index).ok_or_else(|| self.error(&["index"]));
+        }
+        self.skip_blank();
+        let end = self.integer()?;
+        self.skip_blank();
+        let step = if self.eat(':') {
+            self.skip_blank();
+            self.integer()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let start = self.index;
+        let quote = self.bump();
+        let mut literal = String::from("\"");
+        loop {
+            match (quote, self.bump()) {
+                (Some(quote), Some(c)) if c == quote => break,
+                (_, Some('\\')) => match self.bump() {
+                    Some('\'') => literal.push('\''),
+                    Some(c) => {
+                        literal.push('\\');
+                        literal.push(c);
+                    },
+                    None => return Err(self.error(&["escape character"])),
+                },
+                (_, Some('"')) => literal.push_str("\\\""),
+                (_, Some(c)) => literal.push(c),
+                (Some('"'), None) => return Err(self.error(&["'\"'"])),
+                (_, None) => return Err(self.error(&["'\\''"])),
+            }
+        }
+        literal.push('"');
+        StrAutoma::new().start(&mut literal.chars()).map_err(|error| {
+            let expected = match &error {
+                Error::Parser(error) => error.expected().to_vec(),
+                _ => Vec::new(),
+            };
+            let expected = expected.iter().map(|token| token.as_str()).collect::<Vec<_>>();
+            PathError::new(self.chars[start].0, Some(self.chars[start].1), &expected).with_path(self.query).into()
+        })
+    }
+
+    fn logical(&mut self) -> Result<Expr, Error> {
+        let mut terms = vec![self.and()?];
+        loop {
+            self.skip_blank();
+            if self.peek() == Some('|') && self.peek_at(1) == Some('|') {
+                self.index += 2;
+                terms.push(self.and()?);
+            } else if terms.len() == 1 {
+                return Ok(terms.pop().unwrap());
+            } else {
+                return Ok(Expr::Or(terms));
+            }
+        }
+    }
+
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut terms = vec![self.basic()?];
+        loop {
+            self.skip_blank();
+            if self.peek() == Some('&') && self.peek_at(1) == Some('&') {
+                self.index += 2;
+                terms.push(self.basic()?);
+            } else if terms.len() == 1 {
+                return Ok(terms.pop().unwrap());
+            } else {
+                return Ok(Expr::And(terms));
+            }
+        }
+    }
+
+    fn basic(&mut self) -> Result<Expr, Error> {
+        self.skip_blank();
+        if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+            self.bump();
+            self.skip_blank();
+            return match self.peek() {
+                Some('(') => Ok(Expr::Not(Box::new(self.basic()?))),
+                Some('@' | '$') => Ok(Expr::Not(Box::new(Expr::Exists(self.filter_query()?)))),
+                Some(c) if c.is_ascii_lowercase() => Ok(Expr::Not(Box::new(self.basic()?))),
+                _ => Err(self.error(&["'('", "'@'", "'$'", "function"])),
+            };
+        }
+        if self.eat('(') {
+            let expr = self.logical()?;
+            self.skip_blank();
+            self.expect(')', "')'")?;
+            return Ok(expr);
+        }
+        if let Some(expr) = self.logical_function()? {
+            return Ok(expr);
+        }
+        let start = self.index;
+        let left = self.comparable()?;
+        self.skip_blank();
+        match self.operator() {
+            Some(op) => {
+                self.check_comparable(&left, start)?;
+                self.skip_blank();
+                let start = self.index;
+                let right = self.comparable()?;
+                self.check_comparable(&right, start)?;
+                Ok(Expr::Compare(left, op, right))
+            },
+            None => match left {
+                Comparable::Query(query) => Ok(Expr::Exists(query)),
+                _ => Err(self.error(&["comparison operator"])),
+            },
+        }
+    }
+
+    fn logical_function(&mut self) -> Result<Option<Expr>, Error> {
+        let start = self.index;
+        while self.peek().filter(|c| c.is_ascii_lowercase()).is_some() {
+            self.index += 1;
+        }
+        let word = self.chars[start..self.index].iter().map(|(_, c)| c).collect::<String>();
+        let whole = match word.as_str() {
+            "match" => true,
+            "search" => false,
+            _ => {
+                self.index = start;
+                return Ok(None);
+            },
+        };
+        self.expect('(', "'('")?;
+        self.skip_blank();
+        let value = self.argument()?;
+        self.skip_blank();
+        self.expect(',', "','")?;
+        self.skip_blank();
+        let pattern = self.argument()?;
+        self.skip_blank();
+        self.expect(')', "')'")?;
+        Ok(Some(Expr::Match(value, pattern, whole)))
+    }
+
+    fn argument(&mut self) -> Result<Comparable, Error> {
+        let start = self.index;
+        let comparable = self.comparable()?;
+        self.check_comparable(&comparable, start)?;
+        Ok(comparable)
+    }
+
+    fn check_comparable(&self, comparable: &Comparable, start: usize) -> Result<(), Error> {
+        match comparable {
+            Comparable::Query(query) if !query.is_singular() => {
+                let (offset, c) = self.chars[start];
+                Err(PathError::new(offset, Some(c), &["singular query"]).with_path(self.query).into())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn operator(&mut self) -> Option<CompareOp> {
+        let (op, width) = match (self.peek(), self.peek_at(1)) {
+            (Some('='), Some('=')) => (CompareOp::Eq, 2),
+            (Some('!'), Some('=')) => (CompareOp::Ne, 2),
+            (Some('<'), Some('=')) => (CompareOp::Le, 2),
+            (Some('>'), Some('=')) => (CompareOp::Ge, 2),
+            (Some('<'), _) => (CompareOp::Lt, 1),
+            (Some('>'), _) => (CompareOp::Gt, 1),
+            _ => return None,
+        };
+        self.index += width;
+        Some(op)
+    }
+
+    fn filter_query(&mut self) -> Result<FilterQuery, Error> {
+        let relative = self.bump() == Some('@');
+        Ok(FilterQuery {
+            relative,
+            segments: self.segments()?,
+        })
+    }
+
+    fn comparable(&mut self) -> Result<Comparable, Error> {
+        match self.peek() {
+            Some('@' | '$') => Ok(Comparable::Query(self.filter_query()?)),
+            Some('\'' | '"') => Ok(Comparable::Literal(self.string()?.into())),
+            Some(c) if c == '-' || is_number(c) => {
+                let start = self.index;
+                while self.peek().filter(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E') || is_number(*c)).is_some() {
+                    self.index += 1;
+                }
+                let text = self.chars[start..self.index].iter().map(|(_, c)| c).collect::<String>();
+                match parser_value(text.chars()) {
+                    Ok(number @ TypeJson::Number(_)) => Ok(Comparable::Literal(number)),
+                    _ => {
+                        self.index = start;
+                        Err(self.error(&["number"]))
+                    },
+                }
+            },
+            Some(c) if c.is_ascii_lowercase() => {
+                let start = self.index;
+                while self.peek().filter(|c| c.is_ascii_lowercase() || *c == '_').is_some() {
+                    self.index += 1;
+                }
+                let word = self.chars[start..self.index].iter().map(|(_, c)| c).collect::<String>();
+                match word.as_str() {
+                    "true" => Ok(Comparable::Literal(true.into())),
+                    "false" => Ok(Comparable::Literal(false.into())),
+                    "null" => Ok(Comparable::Literal(TypeJson::Null)),
+                    "length" | "count" | "value" => {
+                        self.expect('(', "'('")?;
+                        self.skip_blank();
+                        let argument = self.index;
+                        let function = match word.as_str() {
+                            "length" => {
+                                let comparable = self.comparable()?;
+                                self.check_comparable(&comparable, argument)?;
+                                Function::Length(Box::new(comparable))
+                            },
+                            _ if !matches!(self.peek(), Some('@' | '$')) => return Err(self.error(&["'@'", "'$'"])),
+                            "count" => Function::Count(self.filter_query()?),
+                            _ => Function::Value(self.filter_query()?),
+                        };
+                        self.skip_blank();
+                        self.expect(')', "')'")?;
+                        Ok(Comparable::Function(function))
+                    },
+                    _ => {
+                        self.index = start;
+                        Err(self.error(&["literal", "function"]))
+                    },
+                }
+            },
+            _ => Err(self.error(&["'@'", "'$'", "literal", "function"])),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    is_char(c) || is_number(c)
+}
+
+fn apply<'a>(segments: &[Segment], current: &'a TypeJson, root: &'a TypeJson) -> Vec<&'a TypeJson> {
+    let mut nodes = vec![current];
+    for segment in segments {
+        let mut next = Vec::new();
+        for node in nodes {
+            if segment.descendant {
+                for node in descendants(node) {
+                    select(&segment.selectors, node, root, &mut next);
+                }
+            } else {
+                select(&segment.selectors, node, root, &mut next);
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+fn children(node: &TypeJson) -> Vec<&TypeJson> {
+    match node {
+        TypeJson::Object(object) => object.iter().map(|(_, value)| value).collect(),
+        TypeJson::List(list) => list.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn descendants(node: &TypeJson) -> Vec<&TypeJson> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        nodes.push(node);
+        stack.extend(children(node).into_iter().rev());
+    }
+    nodes
+}
+
+fn select<'a>(selectors: &[Selector], node: &'a TypeJson, root: &'a TypeJson, out: &mut Vec<&'a TypeJson>) {
+    for selector in selectors {
+        match (selector, node) {
+            (Selector::Name(name), TypeJson::Object(object)) => out.extend(object.get(name)),
+            (Selector::Wildcard, node) => out.extend(children(node)),
+            (Selector::Index(index), TypeJson::List(list)) => {
+                let index = if *index < 0 { list.len() as i64 + index } else { *index };
+                if index >= 0 {
+                    out.extend(list.get(index as usize));
+                }
+            },
+            (Selector::Slice(start, end, step), TypeJson::List(list)) => {
+                for index in slice(list.len() as i64, *start, *end, step.unwrap_or(1)) {
+                    out.extend(list.get(index as usize));
+                }
+            },
+            (Selector::Filter(expr), node) => {
+                out.extend(children(node).into_iter().filter(|child| test(expr, child, root)));
+            },
+            _ => {},
+        }
+    }
+}
+
+fn slice(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<i64> {
+    let normalize = |index: i64| if index < 0 { len + index } else { index };
+    let mut indices = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut index = lower;
+        while index < upper {
+            indices.push(index);
+            index += step;
+        }
+    } else if step < 0 {
+        let upper = start.map(normalize).unwrap_or(len - 1).clamp(-1, len - 1);
+        let lower = end.map(normalize).unwrap_or(-1).clamp(-1, len - 1);
+        let mut index = upper;
+        while lower < index {
+            indices.push(index);
+            index += step;
+        }
+    }
+    indices
+}
+
+enum Operand<'s> {
+    Node(&'s TypeJson),
+    Owned(TypeJson),
+}
+
+impl <'s> Operand<'s> {
+    fn get(&self) -> &TypeJson {
+        match self {
+            Operand::Node(node) => node,
+            Operand::Owned(value) => value,
+        }
+    }
+}
+
+fn query_nodes<'s>(query: &FilterQuery, current: &'s TypeJson, root: &'s TypeJson) -> Vec<&'s TypeJson> {
+    apply(&query.segments, if query.relative { current } else { root }, root)
+}
+
+fn test(expr: &Expr, current: &TypeJson, root: &TypeJson) -> bool {
+    match expr {
+        Expr::Or(terms) => terms.iter().any(|term| test(term, current, root)),
+        Expr::And(terms) => terms.iter().all(|term| test(term, current, root)),
+        Expr::Not(expr) => !test(expr, current, root),
+        Expr::Exists(query) => !query_nodes(query, current, root).is_empty(),
+        Expr::Compare(left, op, right) => {
+            let left = operand(left, current, root);
+            let right = operand(right, current, root);
+            let left = left.as_ref().map(|operand| operand.get());
+            let right = right.as_ref().map(|operand| operand.get());
+            match op {
+                CompareOp::Eq => equal_operands(left, right),
+                CompareOp::Ne => !equal_operands(left, right),
+                CompareOp::Lt => less(left, right),
+                CompareOp::Le => less(left, right) || equal_operands(left, right),
+                CompareOp::Gt => less(right, left),
+                CompareOp::Ge => less(right, left) || equal_operands(left, right),
+            }
+        },
+        Expr::Match(value, pattern, whole) => {
+            let value = operand(value, current, root);
+            let pattern = operand(pattern, current, root);
+            match (value.as_ref().map(|operand| operand.get()), pattern.as_ref().map(|operand| operand.get())) {
+                (Some(TypeJson::Text(value)), Some(TypeJson::Text(pattern))) => match Regexp::parse(pattern) {
+                    Some(regexp) if *whole => regexp.is_match(value),
+                    Some(regexp) => regexp.search(value),
+                    None => false,
+                },
+                _ => false,
+            }
+        },
+    }
+}
+
+fn operand<'s>(comparable: &'s Comparable, current: &'s TypeJson, root: &'s TypeJson) -> Option<Operand<'s>> {
+    match comparable {
+        Comparable::Literal(value) => Some(Operand::Node(value)),
+        Comparable::Query(query) => query_nodes(query, current, root).pop().map(Operand::Node),
+        Comparable::Function(Function::Length(argument)) => {
+            let length = match operand(argument, current, root)?.get() {
+                TypeJson::Text(text) => text.chars().count(),
+                TypeJson::List(list) => list.len(),
+                TypeJson::Object(object) => object.len(),
+                _ => return None,
+            };
+            Some(Operand::Owned(length.into()))
+        },
+        Comparable::Function(Function::Count(query)) => Some(Operand::Owned(query_nodes(query, current, root).len().into())),
+        Comparable::Function(Function::Value(query)) => {
+            let mut nodes = query_nodes(query, current, root);
+            match nodes.len() {
+                1 => nodes.pop().map(Operand::Node),
+                _ => None,
+            }
+        },
+    }
+}
+
+fn equal_operands(left: Option<&TypeJson>, right: Option<&TypeJson>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => left == right,
+        _ => false,
+    }
+}
+
+fn less(left: Option<&TypeJson>, right: Option<&TypeJson>) -> bool {
+    match (left, right) {
+        (Some(TypeJson::Number(left)), Some(TypeJson::Number(right))) => left.compare(right) == Some(Ordering::Less),
+        (Some(TypeJson::Text(left)), Some(TypeJson::Text(right))) => left < right,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::objects::*;
+    use crate::query::*;
+
+    fn store() -> TypeJson {
+        crate::parser_value(r#"{
+            "store": {
+                "book": [
+                    {"category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95},
+                    {"category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99},
+                    {"category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99},
+                    {"category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99}
+                ],
+                "bicycle": {"color": "red", "price": 399}
+            },
+            "o": {"j j": {"k.k": 3}, "a'b": 1, "_id": 2, "naïve": 3}
+        }"#.chars()).unwrap()
+    }
+
+    fn select(query: &str) -> Vec<String> {
+        let root = store();
+        JsonPath::parse(query).unwrap().select(&root).iter().map(|node| node.to_string()).collect()
+    }
+
+    #[test]
+    fn query_selectors() {
+        assert_eq!(vec!["\"Nigel Rees\"", "\"Evelyn Waugh\"", "\"Herman Melville\"", "\"J. R. R. Tolkien\""], select("$.store.book[*].author"));
+        assert_eq!(select("$.store.book[*].author"), select("$..author"));
+        assert_eq!(vec!["8.95", "12.99", "8.99", "22.99", "399"], select("$.store..price"));
+        assert_eq!(vec!["\"The Lord of the Rings\""], select("$..book[-1].title"));
+        assert_eq!(vec!["\"Nigel Rees\"", "\"Herman Melville\""], select("$.store.book[0,2].author"));
+        assert_eq!(vec!["\"Nigel Rees\"", "\"Evelyn Waugh\""], select("$..book[:2].author"));
+        assert_eq!(vec!["\"J. R. R. Tolkien\"", "\"Evelyn Waugh\""], select("$..book[::-2].author"));
+        assert_eq!(vec!["\"Sword of Honour\"", "\"Moby Dick\""], select("$..book[1:3].title"));
+        assert!(select("$..book[3:1]").is_empty());
+        assert!(select("$..book[::0]").is_empty());
+        assert_eq!(vec!["\"red\"", "399"], select("$.store.bicycle.*"));
+        assert_eq!(vec!["1", "2"], select("$.o['a\\'b', \"_id\"]"));
+        assert_eq!(vec!["2"], select(".o._id"));
+        assert_eq!(vec!["3"], select("$ .o .naïve"));
+        assert_eq!(vec!["3"], select("$.o['j j']['k.k']"));
+        assert_eq!(vec!["3"], select(".o.\"j j\"[\"k.k\"]"));
+        assert_eq!(1, select("$").len());
+        assert_eq!(2, select("$.store.book[?@.isbn].title").len());
+    }
+
+    #[test]
+    fn query_filters() {
+        assert_eq!(vec!["\"Sayings of the Century\"", "\"Moby Dick\""], select("$..book[?(@.price < 10)].title"));
+        assert_eq!(vec!["\"Sword of Honour\"", "\"The Lord of the Rings\""], select("$..book[?@.price >= 10 && @.category == 'fiction'].title"));
+        assert_eq!(vec!["\"Sayings of the Century\"", "\"The Lord of the Rings\""], select("$..book[?@.category != \"fiction\" || @.price > 20].title"));
+        assert_eq!(vec!["\"Sayings of the Century\"", "\"Sword of Honour\""], select("$..book[?!@.isbn].title"));
+        assert_eq!(vec!["\"Moby Dick\""], select("$..book[?@.price == 8.99].title"));
+        assert_eq!(vec!["{\"color\":\"red\",\"price\":399}"], select("$.store[?@.price == 399.0]"));
+        assert_eq!(vec!["\"Moby Dick\""], select("$..book[?@.author == $.store.book[2].author].title"));
+        assert_eq!(vec!["\"Sword of Honour\""], select("$..book[?length(@.title) == 15].title"));
+        assert_eq!(vec!["\"Moby Dick\"", "\"The Lord of the Rings\""], select("$..book[?count(@.*) == 5].title"));
+        assert_eq!(vec!["\"reference\""], select("$..book[?value(@.price) < 9 && !(@.isbn)].category"));
+        assert!(select("$..book[?@.missing == null]").is_empty());
+        assert_eq!(4, select("$..book[?@.missing == @.other]").len());
+        assert!(select("$..book[?@.title < 1]").is_empty());
+        assert_eq!(vec!["\"Moby Dick\""], select("$..book[?match(@.title, 'M.*k')].title"));
+        assert!(select("$..book[?match(@.title, 'Moby')]").is_empty());
+        assert_eq!(vec!["\"Herman Melville\"", "\"J. R. R. Tolkien\""], select("$..book[?match(@.isbn, '[0-9]-[0-9]{3}-[0-9]{5}-[0-9]')].author"));
+        assert_eq!(vec!["\"Sayings of the Century\"", "\"Sword of Honour\""], select("$..book[?search(@.title, 'o[fu]') && !search(@.author, 'R\\\\.')].title"));
+        assert_eq!(vec!["\"Sayings of the Century\""], select("$..book[?search(@.author, @.category) || match(@.author, 'Nigel.*')].title"));
+        assert_eq!(vec!["\"reference\""], select("$..book[?!match(@.category, 'fict(ion|ive)')].category"));
+        assert!(select("$..book[?match(@.price, '.*')]").is_empty());
+        assert!(select("$..book[?search(@.title, '(')]").is_empty());
+    }
+
+    #[test]
+    fn query_errors() {
+        let assert_error = |query: &str, offset: usize, expected: &[&str]| {
+            match JsonPath::parse(query) {
+                Err(Error::Path(error)) => {
+                    assert_eq!(query, error.path());
+                    assert_eq!(offset, error.offset());
+                    assert_eq!(expected, error.expected());
+                },
+                _ => assert!(false),
+            }
+        };
+        assert_error("$.a[", 4, &["string", "'*'", "'?'", "index", "slice"]);
+        assert_error("$.a[1 2]", 6, &["','", "']'"]);
+        assert_error("$.a['x", 6, &["'\\''"]);
+        assert_error("$..", 3, &["key character", "'\"'", "'*'", "'['"]);
+        assert_error("$[?@..a == 1]", 3, &["singular query"]);
+        assert_error("$[?@.a == tru]", 10, &["literal", "function"]);
+        assert_error("$[?1]", 4, &["comparison operator"]);
+        assert_error("$.a b", 4, &["'.'", "'['", "end of query"]);
+        assert_error("$[?match(@.a)]", 12, &["','"]);
+        assert_error("$[?search(@..a, 'x')]", 10, &["singular query"]);
+    }
+}
@@ -0,0 +1,483 @@
+use std::fmt;
+use std::io;
+
+use serde::ser::{self, Serialize};
+
+use crate::de::NUMBER_TOKEN;
+use crate::error::Error;
+use crate::objects::{ListJson, Number, ObjectJson, TypeJson};
+use crate::serializer::write_escaped;
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl Serialize for TypeJson {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            TypeJson::Object(obj) => obj.serialize(serializer),
+            TypeJson::List(list) => list.serialize(serializer),
+            TypeJson::Text(text) => serializer.serialize_str(text),
+            TypeJson::Number(number) => number.serialize(serializer),
+            TypeJson::Boolean(value) => serializer.serialize_bool(*value),
+            TypeJson::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
+impl Serialize for ObjectJson {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl Serialize for ListJson {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_integer() {
+            if let Some(value) = self.as_u64() {
+                return serializer.serialize_u64(value);
+            }
+            if let Some(value) = self.as_i64() {
+                return serializer.serialize_i64(value);
+            }
+        }
+        let text = self.to_string();
+        match self.as_f64() {
+            Some(value) if decimal(&format!("{:e}", value)) == decimal(&text) => serializer.serialize_f64(value),
+            _ => serializer.serialize_newtype_struct(NUMBER_TOKEN, &text),
+        }
+    }
+}
+
+fn decimal(text: &str) -> (bool, String, i64) {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], text[i + 1..].parse::<i64>().unwrap_or(0)),
+        None => (text, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer, fraction);
+    let significant = digits.trim_start_matches('0');
+    let exponent = exponent + integer.len() as i64 - (digits.len() - significant.len()) as i64;
+    match significant.trim_end_matches('0') {
+        "" => (negative, String::new(), 0),
+        significant => (negative, String::from(significant), exponent),
+    }
+}
+
+pub struct SerdeSerializer<W> {
+    writer: W,
+    raw: bool,
+}
+
+impl <W: io::Write> SerdeSerializer<W> {
+    pub fn new(writer: W) -> SerdeSerializer<W> {
+        SerdeSerializer {
+            writer,
+            raw: false,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_str(&mut self, text: &str) -> Result<(), Error> {
+        let mut escaped = String::with_capacity(text.len() + 2);
+        write_escaped(&mut escaped, text).map_err(<Error as ser::Error>::custom)?;
+        Ok(self.writer.write_all(escaped.as_bytes())?)
+    }
+
+    fn write_number(&mut self, number: Number) -> Result<(), Error> {
+        Ok(write!(self.writer, "{}", number)?)
+    }
+
+    fn begin_variant(&mut self, variant: &str) -> Result<(), Error> {
+        self.writer.write_all(b"{")?;
+        self.write_str(variant)?;
+        Ok(self.writer.write_all(b":")?)
+    }
+
+    fn compound(&mut self, open: &[u8], variant: bool) -> Result<Compound<'_, W>, Error> {
+        self.writer.write_all(open)?;
+        Ok(Compound {
+            ser: self,
+            first: true,
+            variant,
+        })
+    }
+}
+
+pub fn serialize_to_writer<T: Serialize + ?Sized>(writer: impl io::Write, value: &T) -> Result<(), Error> {
+    value.serialize(&mut SerdeSerializer::new(writer))
+}
+
+pub fn serialize_to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let mut serializer = SerdeSerializer::new(Vec::new());
+    value.serialize(&mut serializer)?;
+    String::from_utf8(serializer.into_inner()).map_err(<Error as ser::Error>::custom)
+}
+
+impl <'a, W: io::Write> ser::Serializer for &'a mut SerdeSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, value: bool) -> Result<(), Error> {
+        Ok(write!(self.writer, "{}", value)?)
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), Error> {
+        self.write_number(value.into())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), Error> {
+        if !value.is_finite() {
+            return Err(ser::Error::custom("non-finite numbers are not valid JSON"));
+        }
+        self.write_number(value.into())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), Error> {
+        if !value.is_finite() {
+            return Err(ser::Error::custom("non-finite numbers are not valid JSON"));
+        }
+        self.write_number(value.into())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), Error> {
+        self.write_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), Error> {
+        match self.raw {
+            true => Ok(self.writer.write_all(value.as_bytes())?),
+            false => self.write_str(value),
+        }
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        self.collect_seq(value)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(self.writer.write_all(b"null")?)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), Error> {
+        self.write_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<(), Error> {
+        self.raw = name == NUMBER_TOKEN;
+        let result = value.serialize(&mut *self);
+        self.raw = false;
+        result
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<(), Error> {
+        self.begin_variant(variant)?;
+        value.serialize(&mut *self)?;
+        Ok(self.writer.write_all(b"}")?)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        self.compound(b"[", false)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.compound(b"[", false)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.compound(b"[", false)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.begin_variant(variant)?;
+        self.compound(b"[", true)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        self.compound(b"{", false)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.compound(b"{", false)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.begin_variant(variant)?;
+        self.compound(b"{", true)
+    }
+}
+
+pub struct Compound<'a, W> {
+    ser: &'a mut SerdeSerializer<W>,
+    first: bool,
+    variant: bool,
+}
+
+impl <'a, W: io::Write> Compound<'a, W> {
+    fn separator(&mut self) -> Result<(), Error> {
+        if !self.first {
+            self.ser.writer.write_all(b",")?;
+        }
+        self.first = false;
+        Ok(())
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.separator()?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        self.separator()?;
+        self.ser.write_str(key)?;
+        self.ser.writer.write_all(b":")?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn close(self, close: &[u8]) -> Result<(), Error> {
+        self.ser.writer.write_all(close)?;
+        if self.variant {
+            self.ser.writer.write_all(b"}")?;
+        }
+        Ok(())
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeSeq for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"]")
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeTuple for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"]")
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeTupleStruct for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"]")
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeTupleVariant for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"]")
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeMap for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.separator()?;
+        let key = serialize_to_string(key)?;
+        if key.starts_with('"') {
+            self.ser.writer.write_all(key.as_bytes())?;
+        } else if key.bytes().all(|byte| byte.is_ascii_digit() || byte == b'-') {
+            write!(self.ser.writer, "\"{}\"", key)?;
+        } else {
+            return Err(ser::Error::custom("object keys must be strings or integers"));
+        }
+        Ok(self.ser.writer.write_all(b":")?)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"}")
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeStruct for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"}")
+    }
+}
+
+impl <'a, W: io::Write> ser::SerializeStructVariant for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close(b"}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use crate::ser::*;
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: u32, h: u32 },
+        Pair(i8, i8),
+    }
+
+    #[derive(Serialize)]
+    struct Config {
+        name: &'static str,
+        port: u16,
+        ratio: f32,
+        debug: Option<bool>,
+        tags: Vec<&'static str>,
+        limits: BTreeMap<i32, char>,
+        shapes: Vec<Shape>,
+        unit: (),
+    }
+
+    #[test]
+    fn serialize_structs() {
+        let config = Config {
+            name: "s\"vc\n",
+            port: 8080,
+            ratio: 12.1,
+            debug: None,
+            tags: vec!["a"],
+            limits: BTreeMap::from([(-1, 'x'), (2, 'y')]),
+            shapes: vec![Shape::Empty, Shape::Circle(2.0), Shape::Rect { w: 2, h: 3 }, Shape::Pair(-1, 1)],
+            unit: (),
+        };
+        let text = serialize_to_string(&config).unwrap();
+        assert_eq!(concat!(
+            r#"{"name":"s\"vc\n","port":8080,"ratio":12.1,"debug":null,"tags":["a"],"limits":{"-1":"x","2":"y"},"#,
+            r#""shapes":["Empty",{"Circle":2.0},{"Rect":{"w":2,"h":3}},{"Pair":[-1,1]}],"unit":null}"#,
+        ), text);
+        assert!(crate::from_str(&text).is_ok());
+
+        let mut out = Vec::new();
+        serialize_to_writer(&mut out, &[1.5, -0.25]).unwrap();
+        assert_eq!(b"[1.5,-0.25]", &out[..]);
+
+        assert!(serialize_to_string(&f64::NAN).is_err());
+        assert!(serialize_to_string(&BTreeMap::from([(vec![1], 1)])).is_err());
+    }
+
+    #[test]
+    fn serialize_values() {
+        let input = r#"{"a":[1,2.5,-3,true,null,"x"],"b":{},"c":18446744073709551615}"#;
+        let value = crate::from_str(input).unwrap();
+        assert_eq!(input, serialize_to_string(&value).unwrap());
+        assert_eq!(r#"[1,2.5,-3,true,null,"x"]"#, serialize_to_string(value.as_object().unwrap().get("a").unwrap().as_list().unwrap()).unwrap());
+        let back: TypeJson = crate::deserialize_str(&serialize_to_string(value.as_object().unwrap()).unwrap()).unwrap();
+        assert!(back == value);
+
+        let input = "[1e400,-2E-500,123456789012345678901234567890]";
+        let value = crate::from_str(input).unwrap();
+        assert_eq!(value.to_string(), serialize_to_string(&value).unwrap());
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!(input, serialize_to_string(&value).unwrap());
+        assert_eq!("[0.1,1500.0,1.5,-0]", serialize_to_string(&crate::from_str("[0.1, 1500.0, 15e-1, -0]").unwrap()).unwrap());
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::io;
+
+use crate::error::Error;
+use crate::objects::{ListJson, ObjectJson, TypeJson};
+use crate::pretty::PrettyPrinter;
+
+pub struct Serializer<'a, W> {
+    writer: W,
+    pretty: Option<&'a PrettyPrinter>,
+}
+
+impl <'a, W: fmt::Write> Serializer<'a, W> {
+    pub fn new(writer: W) -> Serializer<'a, W> {
+        Serializer {
+            writer,
+            pretty: None,
+        }
+    }
+
+    pub fn pretty(writer: W, printer: &'a PrettyPrinter) -> Serializer<'a, W> {
+        Serializer {
+            writer,
+            pretty: Some(printer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    pub fn serialize(&mut self, json: &TypeJson) -> fmt::Result {
+        self.write_value(json, 0)?;
+        self.finish()
+    }
+
+    pub fn serialize_object(&mut self, object: &ObjectJson) -> fmt::Result {
+        self.write_object(object, 0)?;
+        self.finish()
+    }
+
+    pub fn serialize_list(&mut self, list: &ListJson) -> fmt::Result {
+        self.write_list(list, 0)?;
+        self.finish()
+    }
+
+    fn finish(&mut self) -> fmt::Result {
+        match self.pretty {
+            Some(printer) if printer.has_trailing_new_line() => printer.write_new_line(&mut self.writer, 0),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_value(&mut self, json: &TypeJson, level: usize) -> fmt::Result {
+        match json {
+            TypeJson::Object(object) => self.write_object(object, level),
+            TypeJson::List(list) => self.write_list(list, level),
+            TypeJson::Text(txt) => write_escaped(&mut self.writer, txt),
+            TypeJson::Number(num) => write!(self.writer, "{}", num),
+            TypeJson::Boolean(b) => write!(self.writer, "{}", b),
+            TypeJson::Null => self.writer.write_str("null"),
+        }
+    }
+
+    fn write_object(&mut self, object: &ObjectJson, level: usize) -> fmt::Result {
+        if object.is_empty() {
+            return self.writer.write_str("{}");
+        }
+        self.writer.write_char('{')?;
+        for (i, (key, value)) in object.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_char(',')?;
+            }
+            self.write_new_line(level + 1)?;
+            write_escaped(&mut self.writer, key)?;
+            self.writer.write_str(if self.pretty.is_some() { ": " } else { ":" })?;
+            self.write_value(value, level + 1)?;
+        }
+        self.write_new_line(level)?;
+        self.writer.write_char('}')
+    }
+
+    fn write_list(&mut self, list: &ListJson, level: usize) -> fmt::Result {
+        if list.len() == 0 {
+            return self.writer.write_str("[]");
+        }
+        let inline = match self.pretty {
+            Some(printer) => printer.is_inline(list),
+            None => true,
+        };
+        self.writer.write_char('[')?;
+        for (i, value) in list.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_char(',')?;
+                if inline && self.pretty.is_some() {
+                    self.writer.write_char(' ')?;
+                }
+            }
+            if inline {
+                self.write_value(value, level)?;
+            } else {
+                self.write_new_line(level + 1)?;
+                self.write_value(value, level + 1)?;
+            }
+        }
+        if !inline {
+            self.write_new_line(level)?;
+        }
+        self.writer.write_char(']')
+    }
+
+    fn write_new_line(&mut self, level: usize) -> fmt::Result {
+        match self.pretty {
+            Some(printer) => printer.write_new_line(&mut self.writer, level),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn write_escaped(writer: &mut impl fmt::Write, txt: &str) -> fmt::Result {
+    writer.write_char('"')?;
+    let mut start = 0;
+    for (i, c) in txt.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            control if control < ' ' => "",
+            _ => continue,
+        };
+        writer.write_str(&txt[start..i])?;
+        if escape.is_empty() {
+            write!(writer, "\\u{:04x}", c as u32)?;
+        } else {
+            writer.write_str(escape)?;
+        }
+        start = i + c.len_utf8();
+    }
+    writer.write_str(&txt[start..])?;
+    writer.write_char('"')
+}
+
+struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl <W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+fn io_serialize<W: io::Write>(writer: W, serialize: impl FnOnce(&mut IoWriter<W>) -> fmt::Result) -> io::Result<()> {
+    let mut writer = IoWriter {
+        inner: writer,
+        error: None,
+    };
+    match serialize(&mut writer) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(writer.error.unwrap_or_else(|| io::Error::other("formatter error"))),
+    }
+}
+
+pub fn to_writer(writer: impl io::Write, json: &TypeJson) -> Result<(), Error> {
+    Ok(io_serialize(writer, |writer| Serializer::new(writer).serialize(json))?)
+}
+
+pub fn to_writer_pretty(writer: impl io::Write, json: &TypeJson, printer: &PrettyPrinter) -> Result<(), Error> {
+    Ok(io_serialize(writer, |writer| Serializer::pretty(writer, printer).serialize(json))?)
+}
+
+impl fmt::Display for TypeJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Serializer::new(f).write_value(self, 0)
+    }
+}
+
+impl fmt::Display for ObjectJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Serializer::new(f).write_object(self, 0)
+    }
+}
+
+impl fmt::Display for ListJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Serializer::new(f).write_list(self, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::error::Error;
+    use crate::objects::*;
+    use crate::pretty::*;
+    use crate::serializer::*;
+
+    struct FailingWriter {
+        budget: usize,
+    }
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.budget < buf.len() {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"));
+            }
+            self.budget -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn sample() -> TypeJson {
+        let mut root = object();
+        root.set("k\"1", "v\n1");
+        let list = root.list("list");
+        list.add(1);
+        list.add(2.5);
+        list.add(null());
+        list.object().set("b", false);
+        root.into()
+    }
+
+    #[test]
+    fn serialize_fmt() {
+        let mut out = String::new();
+        Serializer::new(&mut out).serialize(&sample()).unwrap();
+        assert_eq!(r#"{"k\"1":"v\n1","list":[1,2.5,null,{"b":false}]}"#, out);
+        assert_eq!(out, sample().to_string());
+        assert_eq!(out, format!("{}", sample()));
+
+        let mut out = String::new();
+        Serializer::pretty(&mut out, &PrettyPrinter::new().compact_arrays(true)).serialize(&"x".into()).unwrap();
+        assert_eq!("\"x\"", out);
+    }
+
+    #[test]
+    fn serialize_io() {
+        let mut out = Vec::new();
+        to_writer(&mut out, &sample()).unwrap();
+        assert_eq!(sample().to_string().as_bytes(), &out[..]);
+
+        let mut out = Vec::new();
+        let printer = PrettyPrinter::new().trailing_new_line(true);
+        to_writer_pretty(&mut out, &sample(), &printer).unwrap();
+        assert_eq!(printer.print(&sample()).as_bytes(), &out[..]);
+
+        match to_writer(FailingWriter { budget: 10 }, &sample()) {
+            Err(Error::Io(error)) => assert_eq!(io::ErrorKind::BrokenPipe, error.kind()),
+            _ => assert!(false),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::io;
+
+use crate::automa::{build_next, Position};
+use crate::decoder::Utf8Decoder;
+use crate::error::Error;
+use crate::objects::TypeJson;
+use crate::pull::PullParser;
+
+pub struct JsonStream<I> {
+    events: PullParser<I>,
+}
+
+impl <I: Iterator<Item=char>> JsonStream<I> {
+    pub fn new(iter: I) -> JsonStream<I> {
+        JsonStream {
+            events: PullParser::multiple(iter),
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.events.position()
+    }
+}
+
+impl <R: io::Read> JsonStream<Utf8Decoder<R>> {
+    pub fn from_reader(reader: R) -> JsonStream<Utf8Decoder<R>> {
+        JsonStream {
+            events: PullParser::multiple_reader(reader),
+        }
+    }
+}
+
+impl <I: Iterator<Item=char>> Iterator for JsonStream<I> {
+    type Item = Result<TypeJson, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        build_next(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::automa::Position;
+    use crate::error::Error;
+    use crate::stream::*;
+
+    fn collect<I: Iterator<Item=char>>(stream: &mut JsonStream<I>) -> Vec<String> {
+        stream.by_ref()
+            .map(|value| match value {
+                Ok(value) => value.to_string(),
+                Err(error) => format!("error: {}", error),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn concatenated_values() {
+        let mut stream = JsonStream::new("{\"a\":1}{\"b\":2}[3]12 -4.5e1\n\"s\"true null\n\t".chars());
+        assert_eq!(vec!["{\"a\":1}", "{\"b\":2}", "[3]", "12", "-4.5e1", "\"s\"", "true", "null"], collect(&mut stream));
+        assert_eq!(Position { line: 3, column: 2, offset: 41 }, stream.position());
+
+        let mut stream = JsonStream::from_reader(&b"1 2 3"[..]);
+        assert_eq!(vec!["1", "2", "3"], collect(&mut stream));
+
+        let mut stream = JsonStream::new("  ".chars());
+        assert!(stream.next().is_none());
+
+        let mut stream = JsonStream::new("[1] {\"a\" 2} [3]".chars());
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(Error::Parser(error))) => assert_eq!(9, error.position().offset),
+            _ => assert!(false),
+        }
+        assert!(stream.next().is_none());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::automa::KeyParseQueryToken;
+use crate::diff::{lcs_pairs, render};
+use crate::objects::{array, object, ListJson, TypeJson};
+
+#[derive(Clone, PartialEq)]
+pub struct Conflict {
+    path: String,
+    base: Option<TypeJson>,
+    ours: Option<TypeJson>,
+    theirs: Option<TypeJson>,
+}
+
+impl Conflict {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn base(&self) -> Option<&TypeJson> {
+        self.base.as_ref()
+    }
+
+    pub fn ours(&self) -> Option<&TypeJson> {
+        self.ours.as_ref()
+    }
+
+    pub fn theirs(&self) -> Option<&TypeJson> {
+        self.theirs.as_ref()
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Base,
+    Value(TypeJson),
+    Remove,
+    Unresolved,
+}
+
+pub trait Resolver {
+    fn resolve(&mut self, conflict: &Conflict) -> Resolution;
+}
+
+impl <F: FnMut(&Conflict) -> Resolution> Resolver for F {
+    fn resolve(&mut self, conflict: &Conflict) -> Resolution {
+        self(conflict)
+    }
+}
+
+pub struct MergeResult {
+    merged: TypeJson,
+    conflicts: Vec<Conflict>,
+}
+
+impl MergeResult {
+    pub fn merged(&self) -> &TypeJson {
+        &self.merged
+    }
+
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    pub fn into_merged(self) -> TypeJson {
+        self.merged
+    }
+}
+
+pub fn merge3(base: &TypeJson, ours: &TypeJson, theirs: &TypeJson) -> MergeResult {
+    merge3_with(base, ours, theirs, &mut |_: &Conflict| Resolution::Unresolved)
+}
+
+pub fn merge3_with(base: &TypeJson, ours: &TypeJson, theirs: &TypeJson, resolver: &mut impl Resolver) -> MergeResult {
+    let mut merge = Merge {
+        resolver,
+        conflicts: Vec::new(),
+        path: Vec::new(),
+    };
+    let merged = merge.values(Some(base), Some(ours), Some(theirs)).unwrap_or_else(|| ours.clone());
+    MergeResult {
+        merged,
+        conflicts: merge.conflicts,
+    }
+}
+
+enum Chunk {
+    Ours,
+    Theirs,
+    Elements,
+}
+
+struct Merge<'a, R> {
+    resolver: &'a mut R,
+    conflicts: Vec<Conflict>,
+    path: Vec<KeyParseQueryToken>,
+}
+
+impl <'a, R: Resolver> Merge<'a, R> {
+    fn values(&mut self, base: Option<&TypeJson>, ours: Option<&TypeJson>, theirs: Option<&TypeJson>) -> Option<TypeJson> {
+        if ours == theirs || base == theirs {
+            return ours.cloned();
+        }
+        if base == ours {
+            return theirs.cloned();
+        }
+        match (base, ours, theirs) {
+            (base, Some(TypeJson::Object(ours)), Some(TypeJson::Object(theirs))) => {
+                let base = base.and_then(|base| base.as_object());
+                let mut merged = object();
+                let keys = ours.keys().chain(theirs.keys().filter(|key| ours.get(key).is_none()));
+                for key in keys {
+                    self.path.push(KeyParseQueryToken::Key(key.clone()));
+                    let value = self.values(base.and_then(|base| base.get(key)), ours.get(key), theirs.get(key));
+                    self.path.pop();
+                    if let Some(value) = value {
+                        merged.set(key, value);
+                    }
+                }
+                Some(merged.into())
+            },
+            (Some(TypeJson::List(base_list)), Some(TypeJson::List(ours_list)), Some(TypeJson::List(theirs_list))) => {
+                match self.lists(base_list, ours_list, theirs_list) {
+                    Some(merged) => Some(merged.into()),
+                    None => self.conflict(base, ours, theirs),
+                }
+            },
+            _ => self.conflict(base, ours, theirs),
+        }
+    }
+
+    fn lists(&mut self, base: &ListJson, ours: &ListJson, theirs: &ListJson) -> Option<ListJson> {
+        let base = base.iter().collect::<Vec<_>>();
+        let ours = ours.iter().collect::<Vec<_>>();
+        let theirs = theirs.iter().collect::<Vec<_>>();
+        let with_theirs = lcs_pairs(&base, &theirs);
+        let mut stable = lcs_pairs(&base, &ours)
+            .into_iter()
+            .filter_map(|(b, o)| with_theirs.binary_search_by_key(&b, |&(b, _)| b).ok().map(|i| (b, o, with_theirs[i].1)))
+            .collect::<Vec<_>>();
+        stable.push((base.len(), ours.len(), theirs.len()));
+
+        let mut chunks = Vec::new();
+        let (mut b0, mut o0, mut t0) = (0, 0, 0);
+        for &(b, o, t) in &stable {
+            let (base, ours, theirs) = (&base[b0..b], &ours[o0..o], &theirs[t0..t]);
+            let chunk = if ours == base {
+                Chunk::Theirs
+            } else if theirs == base || ours == theirs {
+                Chunk::Ours
+            } else if base.len() == ours.len() && base.len() == theirs.len() {
+                Chunk::Elements
+            } else {
+                return None;
+            };
+            chunks.push((chunk, b0..b, o0..o, t0..t));
+            (b0, o0, t0) = (b + 1, o + 1, t + 1);
+        }
+
+        let mut merged = array();
+        for ((chunk, b, o, t), &(_, stable, _)) in chunks.into_iter().zip(&stable) {
+            match chunk {
+                Chunk::Ours => ours[o].iter().for_each(|value| merged.add((*value).clone())),
+                Chunk::Theirs => theirs[t].iter().for_each(|value| merged.add((*value).clone())),
+                Chunk::Elements => for ((b, o), t) in b.zip(o).zip(t) {
+                    self.path.push(KeyParseQueryToken::Index(o));
+                    let value = self.values(Some(base[b]), Some(ours[o]), Some(theirs[t]));
+                    self.path.pop();
+                    if let Some(value) = value {
+                        merged.add(value);
+                    }
+                },
+            }
+            if let Some(value) = ours.get(stable) {
+                merged.add((*value).clone());
+            }
+        }
+        Some(merged)
+    }
+
+    fn conflict(&mut self, base: Option<&TypeJson>, ours: Option<&TypeJson>, theirs: Option<&TypeJson>) -> Option<TypeJson> {
+        let conflict = Conflict {
+            path: render(&self.path),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        };
+        let resolved = match self.resolver.resolve(&conflict) {
+            Resolution::Ours => Some(conflict.ours.clone()),
+            Resolution::Theirs => Some(conflict.theirs.clone()),
+            Resolution::Base => Some(conflict.base.clone()),
+            Resolution::Value(value) => Some(Some(value)),
+            Resolution::Remove => Some(None),
+            Resolution::Unresolved => None,
+        };
+        match resolved {
+            Some(None) if self.path.is_empty() => {},
+            Some(value) => return value,
+            None => {},
+        }
+        let ours = conflict.ours.clone();
+        self.conflicts.push(conflict);
+        ours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::three_way::*;
+
+    fn json(text: &str) -> TypeJson {
+        crate::from_str(text).unwrap()
+    }
+
+    const BASE: &str = r#"{"name": "svc", "port": 80, "tags": ["a", "b"], "limits": {"cpu": 1, "mem": 512}, "debug": false, "old": 1}"#;
+    const OURS: &str = r#"{"name": "svc", "port": 8080, "tags": ["a", "c"], "limits": {"cpu": 2, "mem": 512}, "debug": false, "mine": true}"#;
+    const THEIRS: &str = r#"{"name": "api", "port": 9090, "tags": ["a", "b"], "limits": {"cpu": 1, "mem": 1024}, "old": 1, "yours": true}"#;
+
+    #[test]
+    fn merge_clean_and_conflicts() {
+        let result = merge3(&json(BASE), &json(OURS), &json(THEIRS));
+        assert!(!result.is_clean());
+        assert_eq!(r#"{"name":"api","port":8080,"tags":["a","c"],"limits":{"cpu":2,"mem":1024},"mine":true,"yours":true}"#, result.merged().to_string());
+        assert_eq!(1, result.conflicts().len());
+        let conflict = &result.conflicts()[0];
+        assert_eq!(".port", conflict.path());
+        assert_eq!(Some("80"), conflict.base().map(|value| value.to_string()).as_deref());
+        assert_eq!(Some("8080"), conflict.ours().map(|value| value.to_string()).as_deref());
+        assert_eq!(Some("9090"), conflict.theirs().map(|value| value.to_string()).as_deref());
+
+        let result = merge3(&json(BASE), &json(OURS), &json(OURS));
+        assert!(result.is_clean());
+        assert!(result.into_merged() == json(OURS));
+    }
+
+    #[test]
+    fn merge_resolvers() {
+        let base = json(r#"{"a": 1, "b": {"c": 1}, "list": [1, 2]}"#);
+        let ours = json(r#"{"a": 2, "list": [1, 2, 3]}"#);
+        let theirs = json(r#"{"a": 3, "b": {"c": 2}, "list": [0]}"#);
+
+        let mut paths = Vec::new();
+        let result = merge3_with(&base, &ours, &theirs, &mut |conflict: &Conflict| {
+            paths.push(String::from(conflict.path()));
+            Resolution::Theirs
+        });
+        assert!(result.is_clean());
+        assert_eq!(vec![".a", ".list", ".b"], paths);
+        assert_eq!(r#"{"a":3,"list":[0],"b":{"c":2}}"#, result.merged().to_string());
+
+        let result = merge3_with(&base, &ours, &theirs, &mut |conflict: &Conflict| match conflict.path() {
+            ".a" => Resolution::Value(json("10")),
+            ".b" => Resolution::Remove,
+            _ => Resolution::Base,
+        });
+        assert_eq!(r#"{"a":10,"list":[1,2]}"#, result.merged().to_string());
+
+        struct KeepOurs(usize);
+
+        impl Resolver for KeepOurs {
+            fn resolve(&mut self, _conflict: &Conflict) -> Resolution {
+                self.0 += 1;
+                Resolution::Ours
+            }
+        }
+
+        let mut resolver = KeepOurs(0);
+        let result = merge3_with(&base, &ours, &theirs, &mut resolver);
+        assert!(result.into_merged() == ours);
+        assert_eq!(3, resolver.0);
+
+        let result = merge3(&json("[1, 2]"), &json("[9, 2]"), &json("[1, 8]"));
+        assert_eq!("[9,8]", result.merged().to_string());
+        let result = merge3(&json("1"), &json("\"x\""), &json("true"));
+        assert_eq!("", result.conflicts()[0].path());
+        assert_eq!("\"x\"", result.merged().to_string());
+
+        let result = merge3_with(&json("[1, 2]"), &json("[9, 2]"), &json("[8, 2]"), &mut |_: &Conflict| Resolution::Remove);
+        assert!(result.is_clean());
+        assert_eq!("[2]", result.merged().to_string());
+        let result = merge3_with(&json("[{\"a\": 1}]"), &json("[{}]"), &json("[{\"a\": 2}]"), &mut |_: &Conflict| Resolution::Ours);
+        assert_eq!("[{}]", result.merged().to_string());
+        let result = merge3_with(&json("1"), &json("\"x\""), &json("true"), &mut |_: &Conflict| Resolution::Remove);
+        assert_eq!(1, result.conflicts().len());
+        assert_eq!("\"x\"", result.merged().to_string());
+    }
+
+    #[test]
+    fn merge_arrays() {
+        let result = merge3(&json("[1, 2]"), &json("[1, 2, 3]"), &json("[9, 2]"));
+        assert!(result.is_clean());
+        assert_eq!("[9,2,3]", result.merged().to_string());
+
+        let result = merge3(&json(r#"["a", "b", "c"]"#), &json(r#"["a", "c"]"#), &json(r#"["z", "a", "b", "c", "d"]"#));
+        assert!(result.is_clean());
+        assert_eq!(r#"["z","a","c","d"]"#, result.merged().to_string());
+
+        let result = merge3(&json(r#"[{"id": 1, "n": 0}, "x"]"#), &json(r#"[{"id": 1, "n": 1}, "x", "y"]"#), &json(r#"[{"id": 1, "n": 2}, "x"]"#));
+        assert_eq!(vec!["[0].n"], result.conflicts().iter().map(|conflict| conflict.path()).collect::<Vec<_>>());
+        assert_eq!(r#"[{"id":1,"n":1},"x","y"]"#, result.merged().to_string());
+
+        let result = merge3(&json("[1]"), &json("[1, 2]"), &json("[1, 3]"));
+        assert_eq!(vec![""], result.conflicts().iter().map(|conflict| conflict.path()).collect::<Vec<_>>());
+        assert_eq!("[1,2]", result.merged().to_string());
+    }
+}
@@ -0,0 +1,135 @@
+use std::io;
+
+use crate::error::Error;
+use crate::objects::TypeJson;
+use crate::pull::{JsonEvent, PullParser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue,
+    Skip,
+    Abort,
+}
+
+pub trait Visitor {
+    fn begin_object(&mut self) -> Visit {
+        Visit::Continue
+    }
+
+    fn end_object(&mut self) -> Visit {
+        Visit::Continue
+    }
+
+    fn key(&mut self, _key: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn scalar(&mut self, _value: TypeJson) -> Visit {
+        Visit::Continue
+    }
+
+    fn begin_array(&mut self) -> Visit {
+        Visit::Continue
+    }
+
+    fn end_array(&mut self) -> Visit {
+        Visit::Continue
+    }
+}
+
+pub fn visit(iter: impl Iterator<Item=char>, visitor: &mut impl Visitor) -> Result<bool, Error> {
+    drive(PullParser::new(iter), visitor)
+}
+
+pub fn visit_reader(reader: impl io::Read, visitor: &mut impl Visitor) -> Result<bool, Error> {
+    drive(PullParser::from_reader(reader), visitor)
+}
+
+fn drive<I: Iterator<Item=char>>(mut events: PullParser<I>, visitor: &mut impl Visitor) -> Result<bool, Error> {
+    while let Some(event) = events.next() {
+        let action = match event? {
+            JsonEvent::StartObject => visitor.begin_object(),
+            JsonEvent::EndObject => visitor.end_object(),
+            JsonEvent::Key(key) => visitor.key(&key),
+            JsonEvent::Value(value) => visitor.scalar(value),
+            JsonEvent::StartArray => visitor.begin_array(),
+            JsonEvent::EndArray => visitor.end_array(),
+        };
+        match action {
+            Visit::Continue => {},
+            Visit::Abort => return Ok(false),
+            Visit::Skip => events.skip_subtree()?,
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::*;
+    use crate::visitor::*;
+
+    #[derive(Default)]
+    struct Collect {
+        trace: Vec<String>,
+        skip: &'static str,
+        abort: &'static str,
+    }
+
+    impl Visitor for Collect {
+        fn begin_object(&mut self) -> Visit {
+            self.trace.push(String::from("{"));
+            Visit::Continue
+        }
+
+        fn end_object(&mut self) -> Visit {
+            self.trace.push(String::from("}"));
+            Visit::Continue
+        }
+
+        fn key(&mut self, key: &str) -> Visit {
+            self.trace.push(format!("{}:", key));
+            match key {
+                key if key == self.skip => Visit::Skip,
+                key if key == self.abort => Visit::Abort,
+                _ => Visit::Continue,
+            }
+        }
+
+        fn scalar(&mut self, value: TypeJson) -> Visit {
+            self.trace.push(value.to_string());
+            Visit::Continue
+        }
+
+        fn begin_array(&mut self) -> Visit {
+            self.trace.push(String::from("["));
+            Visit::Skip
+        }
+    }
+
+    const INPUT: &str = r#"{"id": 7, "payload": {"big": [1, 2, 3]}, "tags": ["a"], "name": "x", "tail": true}"#;
+
+    #[test]
+    fn visit_events() {
+        let mut visitor = Collect::default();
+        assert!(visit(INPUT.chars(), &mut visitor).unwrap());
+        assert_eq!(vec!["{", "id:", "7", "payload:", "{", "big:", "[", "}", "tags:", "[", "name:", "\"x\"", "tail:", "true", "}"], visitor.trace);
+
+        let mut visitor = Collect { skip: "payload", abort: "name", ..Collect::default() };
+        assert!(!visit_reader(INPUT.as_bytes(), &mut visitor).unwrap());
+        assert_eq!(vec!["{", "id:", "7", "payload:", "tags:", "[", "name:"], visitor.trace);
+
+        let mut visitor = Collect { skip: "id", ..Collect::default() };
+        assert!(visit(INPUT.chars(), &mut visitor).unwrap());
+        assert_eq!("payload:", visitor.trace[2]);
+
+        let mut visitor = Collect::default();
+        assert!(visit("{\"id\": 7,}".chars(), &mut visitor).is_err());
+
+        for input in [r#"{"a": [1 }, "b": 2}"#, r#"{"a": xyz, "b": 2}"#, r#"{"a": {"b": [1, 2}}, "c": 3}"#] {
+            assert!(visit(input.chars(), &mut Collect::default()).is_err(), "{}", input);
+            assert!(visit(input.chars(), &mut Collect { skip: "a", ..Collect::default() }).is_err(), "{}", input);
+        }
+        assert!(visit(r#"{"a": [1 2], "b": 2}"#.chars(), &mut Collect::default()).is_err());
+    }
+}